@@ -2,6 +2,20 @@ use crate::contract::lib::Error;
 use async_trait::async_trait;
 use std::collections::HashMap;
 
+/// Configuration as served by store.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigPayload {
+    /// Bytes untouched by text decoding.
+    pub body: Vec<u8>,
+    /// Media type reported by store, e.g. `Content-Type` of HTTP response.
+    pub content_type: Option<String>,
+    /// Validator identifying content, e.g. `ETag` or `Last-Modified` of HTTP response.
+    /// Results derived from payload of the same revision can be reused.
+    pub revision: Option<String>,
+    /// Store confirmed content did not change since previous fetch, body is served from cache.
+    pub not_modified: bool,
+}
+
 #[async_trait]
 pub trait IConfigStore: Send + Sync {
     async fn get_config(
@@ -9,4 +23,25 @@ pub trait IConfigStore: Send + Sync {
         query_params: Option<HashMap<String, String>>,
         headers: Option<HashMap<String, String>>,
     ) -> Result<String, Error>;
+
+    /// Fetches configuration with its media type. Stores serving only textual
+    /// documents return them UTF-8 encoded, without media type.
+    async fn get_payload(
+        &self,
+        query_params: Option<HashMap<String, String>>,
+        headers: Option<HashMap<String, String>>,
+    ) -> Result<ConfigPayload, Error> {
+        self.get_config(query_params, headers)
+            .await
+            .map(|config| ConfigPayload {
+                body: config.into_bytes(),
+                ..Default::default()
+            })
+    }
+
+    /// Checks whether the store is able to serve configuration.
+    /// Stores without dedicated health endpoint are considered healthy.
+    async fn health_check(&self) -> Result<(), Error> {
+        Ok(())
+    }
 }
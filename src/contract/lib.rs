@@ -35,6 +35,72 @@ pub enum Error {
     #[error("Http Store Server Error: {0} ")]
     HttpConfigStoreServerError(#[source] std::io::Error),
 
+    #[error("Vault Store Error: {0} ")]
+    VaultConfigStoreError(#[source] reqwest::Error),
+
+    #[error("Vault Store Client Error: {0} ")]
+    VaultConfigStoreClientError(#[source] std::io::Error),
+
+    #[error("Vault Store Server Error: {0} ")]
+    VaultConfigStoreServerError(#[source] std::io::Error),
+
+    #[error("Vault Auth Error: {0} ")]
+    VaultAuthError(#[source] std::io::Error),
+
+    #[error("Secret reference error: {0}")]
+    SecretReferenceError(String),
+
+    #[error("Invalid configuration store: {0}")]
+    InvalidConfigStore(String),
+
+    #[error("Missing configuration store parameter: {0}")]
+    MissingConfigStoreParameter(String),
+
+    #[error("Target {0} does not exist and creation policy does not allow creating it")]
+    TargetNotFound(String),
+
+    #[error("Claimed keys are managed by another field manager: {0}")]
+    TargetApplyConflict(String),
+
+    #[error("Secret data does not match its type: {0}")]
+    InvalidSecretData(String),
+
+    #[error("Invalid selector {0}, expected JSON Pointer or JSONPath starting with $")]
+    InvalidSelector(String),
+
+    #[error("Selector {0} does not match any value in the document")]
+    SelectorNoMatch(String),
+
+    #[error("Invalid data key {0}, expected 1-253 characters of [-._a-zA-Z0-9]")]
+    InvalidDataKey(String),
+
+    #[error("Data key {0} is written by more than one data key of the claim")]
+    DuplicateDataKey(String),
+
+    #[error("Failed to render template: {0}")]
+    TemplateError(String),
+
+    #[error("Failed to parse content as {0}: {1}")]
+    FormatParseError(String, String),
+
+    #[error("No configuration store could serve the data: {0}")]
+    ConfigStoresFailed(String),
+
+    #[error("Failed to apply JSON Patch: {0}")]
+    JsonPatchError(String),
+
+    #[error("Invalid rollout target: {0}")]
+    InvalidRolloutTarget(String),
+
+    #[error("Store response is not valid base64: {0}")]
+    Base64DecodeError(#[source] base64::DecodeError),
+
+    #[error("Config store is rate limiting requests, retry after {0:?}")]
+    ConfigStoreRateLimited(std::time::Duration),
+
+    #[error("Config store circuit breaker is open, next attempt in {0:?}")]
+    ConfigStoreCircuitOpen(std::time::Duration),
+
     #[error("Kube Error: {0}")]
     KubeError(#[source] kube::Error),
 
@@ -44,12 +110,12 @@ pub enum Error {
     #[error("Unsupported configuration file format")]
     UnsupportedFileType(),
 
-    #[error("Another pod holds the lease")]
-    LeaseHeldByAnotherPod(),
-
     #[error("Operation was cancelled")]
     Cancelled,
 
+    #[error("Invalid leader election configuration: {0}")]
+    InvalidLeaderElectionConfig(String),
+
     #[error("Incompatible file formats")]
     IncompatibleFileTypes(),
 
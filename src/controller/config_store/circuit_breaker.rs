@@ -0,0 +1,164 @@
+use crate::contract::iconfigstore::{ConfigPayload, IConfigStore};
+use crate::contract::lib::Error;
+use crate::controller::config_store::retry::is_transient;
+use async_trait::async_trait;
+use log::warn;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub enum CircuitState {
+    Closed,
+    HalfOpen,
+    Open,
+}
+
+impl CircuitState {
+    /// Value exported in `circuit_state` gauge.
+    pub fn as_gauge(&self) -> i64 {
+        match self {
+            CircuitState::Closed => 0,
+            CircuitState::HalfOpen => 1,
+            CircuitState::Open => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct CircuitBreakerOptions {
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerOptions {
+    fn default() -> Self {
+        CircuitBreakerOptions {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+/// Handed out for every fetch let through. Only the permit of the trial fetch decides whether
+/// half open circuit closes, outcome of fetches let through before it opened is dropped.
+#[must_use]
+struct CallPermit {
+    trial: bool,
+}
+
+/// Guards config store against being called while it is known to be unavailable.
+/// After `failure_threshold` consecutive transient failures fetches are short-circuited for
+/// `cooldown`, afterwards single trial fetch is let through to decide whether to close again.
+pub struct CircuitBreakerStore {
+    inner: Arc<dyn IConfigStore>,
+    options: CircuitBreakerOptions,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreakerStore {
+    pub fn new(inner: Arc<dyn IConfigStore>, options: CircuitBreakerOptions) -> Self {
+        CircuitBreakerStore {
+            inner,
+            options,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        let state = self.state.lock().unwrap();
+        match state.opened_at {
+            None => CircuitState::Closed,
+            Some(_) if state.trial_in_flight => CircuitState::HalfOpen,
+            Some(opened_at) if opened_at.elapsed() >= self.options.cooldown => {
+                CircuitState::HalfOpen
+            }
+            Some(_) => CircuitState::Open,
+        }
+    }
+
+    /// Lets fetch through unless circuit is open. Once cooldown passes, trial fetch is let through
+    /// and cooldown starts over, so concurrent fetches keep being short-circuited meanwhile.
+    fn acquire(&self) -> Result<CallPermit, Error> {
+        let mut state = self.state.lock().unwrap();
+        let Some(opened_at) = state.opened_at else {
+            return Ok(CallPermit { trial: false });
+        };
+
+        let elapsed = opened_at.elapsed();
+        if elapsed < self.options.cooldown {
+            return Err(Error::ConfigStoreCircuitOpen(
+                self.options.cooldown - elapsed,
+            ));
+        }
+        state.opened_at = Some(Instant::now());
+        state.trial_in_flight = true;
+        Ok(CallPermit { trial: true })
+    }
+
+    fn record<T>(&self, permit: CallPermit, result: &Result<T, Error>) {
+        let mut state = self.state.lock().unwrap();
+        if permit.trial {
+            state.trial_in_flight = false;
+        } else if state.opened_at.is_some() {
+            return;
+        }
+
+        match result {
+            // Client errors still mean the store is up and answering
+            Err(e) if is_transient(e) => {
+                state.consecutive_failures += 1;
+                if permit.trial || state.consecutive_failures >= self.options.failure_threshold {
+                    warn!(
+                        "Opening circuit breaker after {} consecutive failures: {}",
+                        state.consecutive_failures, e
+                    );
+                    state.opened_at = Some(Instant::now());
+                }
+            }
+            _ => {
+                state.consecutive_failures = 0;
+                state.opened_at = None;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl IConfigStore for CircuitBreakerStore {
+    async fn get_config(
+        &self,
+        query_params: Option<HashMap<String, String>>,
+        headers: Option<HashMap<String, String>>,
+    ) -> Result<String, Error> {
+        let permit = self.acquire()?;
+        let result = self.inner.get_config(query_params, headers).await;
+        self.record(permit, &result);
+        result
+    }
+
+    async fn get_payload(
+        &self,
+        query_params: Option<HashMap<String, String>>,
+        headers: Option<HashMap<String, String>>,
+    ) -> Result<ConfigPayload, Error> {
+        let permit = self.acquire()?;
+        let result = self.inner.get_payload(query_params, headers).await;
+        self.record(permit, &result);
+        result
+    }
+
+    async fn health_check(&self) -> Result<(), Error> {
+        self.inner.health_check().await
+    }
+}
@@ -0,0 +1,214 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::iconfigstore::IConfigStore;
+    use crate::contract::lib::Error;
+    use crate::controller::config_store::circuit_breaker::{
+        CircuitBreakerOptions, CircuitBreakerStore, CircuitState,
+    };
+    use crate::controller::config_store::store_registry::ConfigStoreRegistry;
+    use crate::controller::v1alpha1::crd::configuration_store::{
+        ConfigurationSourceStatus, ConfigurationStore,
+    };
+    use async_trait::async_trait;
+    use kube::Resource;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Store failing with server or client errors until marked healthy.
+    #[derive(Default)]
+    struct FlakyStore {
+        healthy: AtomicBool,
+        client_errors: AtomicBool,
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl IConfigStore for FlakyStore {
+        async fn get_config(
+            &self,
+            _query_params: Option<HashMap<String, String>>,
+            _headers: Option<HashMap<String, String>>,
+        ) -> Result<String, Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.healthy.load(Ordering::SeqCst) {
+                Ok(String::from("{}"))
+            } else if self.client_errors.load(Ordering::SeqCst) {
+                Err(Error::HttpConfigStoreClientError(std::io::Error::other(
+                    "not found",
+                )))
+            } else {
+                Err(Error::HttpConfigStoreServerError(std::io::Error::other(
+                    "unavailable",
+                )))
+            }
+        }
+    }
+
+    /// Store answering each fetch after given delay, healthy or failing as scripted.
+    struct ScriptedStore {
+        responses: Mutex<VecDeque<(Duration, bool)>>,
+    }
+
+    #[async_trait]
+    impl IConfigStore for ScriptedStore {
+        async fn get_config(
+            &self,
+            _query_params: Option<HashMap<String, String>>,
+            _headers: Option<HashMap<String, String>>,
+        ) -> Result<String, Error> {
+            let (delay, healthy) = self.responses.lock().unwrap().pop_front().unwrap();
+            tokio::time::sleep(delay).await;
+            if healthy {
+                Ok(String::from("{}"))
+            } else {
+                Err(Error::HttpConfigStoreServerError(std::io::Error::other(
+                    "unavailable",
+                )))
+            }
+        }
+    }
+
+    fn breaker<S: IConfigStore + 'static>(inner: Arc<S>) -> CircuitBreakerStore {
+        CircuitBreakerStore::new(
+            inner,
+            CircuitBreakerOptions {
+                failure_threshold: 3,
+                cooldown: Duration::from_millis(100),
+            },
+        )
+    }
+
+    async fn fail_times(store: &CircuitBreakerStore, times: u32) {
+        for _ in 0..times {
+            assert!(store.get_config(None, None).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_circuit_opens_after_consecutive_failures() {
+        let inner = Arc::new(FlakyStore::default());
+        let store = breaker(inner.clone());
+
+        fail_times(&store, 3).await;
+        assert_eq!(store.state(), CircuitState::Open);
+
+        match store.get_config(None, None).await {
+            Err(Error::ConfigStoreCircuitOpen(_)) => {}
+            _ => panic!("Expected Error::ConfigStoreCircuitOpen"),
+        }
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_successful_trial_after_cooldown_closes_circuit() {
+        let inner = Arc::new(FlakyStore::default());
+        let store = breaker(inner.clone());
+        fail_times(&store, 3).await;
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(store.state(), CircuitState::HalfOpen);
+
+        inner.healthy.store(true, Ordering::SeqCst);
+        store
+            .get_config(None, None)
+            .await
+            .expect("Trial fetch should be let through");
+        assert_eq!(store.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_failed_trial_opens_circuit_again() {
+        let inner = Arc::new(FlakyStore::default());
+        let store = breaker(inner.clone());
+        fail_times(&store, 3).await;
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        fail_times(&store, 1).await;
+
+        assert_eq!(store.state(), CircuitState::Open);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_only_trial_fetch_decides_half_open_circuit() {
+        let inner = Arc::new(ScriptedStore {
+            responses: Mutex::new(VecDeque::from([
+                // Let through before circuit opened, succeeds while trial is in flight
+                (Duration::from_millis(300), true),
+                (Duration::ZERO, false),
+                (Duration::ZERO, false),
+                (Duration::ZERO, false),
+                // Trial
+                (Duration::from_millis(300), false),
+            ])),
+        });
+        let store = breaker(inner);
+
+        let (early, trial) = tokio::join!(store.get_config(None, None), async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            fail_times(&store, 3).await;
+            tokio::time::sleep(Duration::from_millis(120)).await;
+            store.get_config(None, None).await
+        });
+
+        assert!(early.is_ok());
+        assert!(trial.is_err());
+        assert_eq!(store.state(), CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_do_not_open_circuit() {
+        let inner = Arc::new(FlakyStore::default());
+        inner.client_errors.store(true, Ordering::SeqCst);
+        let store = breaker(inner.clone());
+
+        fail_times(&store, 5).await;
+
+        assert_eq!(store.state(), CircuitState::Closed);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_survives_status_write() {
+        let registry = ConfigStoreRegistry::default();
+        let options = CircuitBreakerOptions {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(60),
+        };
+        let inner = Arc::new(FlakyStore::default());
+        let mut store: ConfigurationStore = serde_json::from_value(json!({
+            "apiVersion": "external-config.com/v1alpha1",
+            "kind": "ConfigurationStore",
+            "metadata": { "name": "store", "uid": "uid-1", "generation": 1, "resourceVersion": "1" },
+            "spec": { "provider": { "http": { "baseUrl": "http://store" } } }
+        }))
+        .unwrap();
+
+        let breaker = registry.get_or_build("uid-1", store.meta().generation, 1, &options, || {
+            inner.clone()
+        });
+        fail_times(&breaker, 3).await;
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // Reporting circuit state bumps resourceVersion of the store
+        store.status = Some(ConfigurationSourceStatus {
+            circuit_state: Some(CircuitState::Open),
+            ..Default::default()
+        });
+        store.metadata.resource_version = Some("2".to_string());
+        let breaker = registry.get_or_build("uid-1", store.meta().generation, 1, &options, || {
+            Arc::new(FlakyStore::default())
+        });
+
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(matches!(
+            breaker.get_config(None, None).await,
+            Err(Error::ConfigStoreCircuitOpen(_))
+        ));
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+    }
+}
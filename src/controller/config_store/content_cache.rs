@@ -0,0 +1,131 @@
+use crate::controller::utils::file_format::ConfigFormat;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+/// Documents kept at most, across all stores.
+pub const MAX_PARSED_DOCUMENTS: usize = 1024;
+
+/// Map holding at most `capacity` entries. Inserting new key into full map drops the entry
+/// inserted longest ago, so caches keyed by claim supplied params cannot grow without limit.
+pub struct BoundedMap<K, V> {
+    capacity: usize,
+    entries: HashMap<K, (u64, V)>,
+    next_sequence: u64,
+}
+
+impl<K: Eq + Hash + Clone, V> BoundedMap<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            next_sequence: 0,
+        }
+    }
+
+    pub fn get<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.entries.get(key).map(|(_, value)| value)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (sequence, _))| *sequence)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (self.next_sequence, value));
+        self.next_sequence += 1;
+    }
+
+    pub fn remove<Q: Eq + Hash + ?Sized>(&mut self, key: &Q)
+    where
+        K: Borrow<Q>,
+    {
+        self.entries.remove(key);
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&K) -> bool) {
+        self.entries.retain(|key, _| keep(key));
+    }
+}
+
+/// Identifies document parsed from store response. Store is identified by uid and generation,
+/// so store recreated under the same name or with changed spec does not serve documents
+/// parsed from responses of its predecessor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParsedContentKey {
+    pub store_uid: String,
+    pub generation: Option<i64>,
+    /// Store reference, its params and input format
+    pub request: String,
+}
+
+struct ParsedContent {
+    revision: String,
+    config: ConfigFormat,
+}
+
+/// Documents parsed from store payloads, keyed by store, its params and input format.
+/// Document is reused while store serves the same revision, so unchanged content is not
+/// parsed again on every refresh.
+pub struct ParsedContentCache {
+    entries: Mutex<BoundedMap<ParsedContentKey, ParsedContent>>,
+}
+
+impl Default for ParsedContentCache {
+    fn default() -> Self {
+        Self::with_capacity(MAX_PARSED_DOCUMENTS)
+    }
+}
+
+impl ParsedContentCache {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(BoundedMap::new(capacity)),
+        }
+    }
+
+    pub fn get(&self, key: &ParsedContentKey, revision: Option<&str>) -> Option<ConfigFormat> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|entry| Some(entry.revision.as_str()) == revision)
+            .map(|entry| entry.config.clone())
+    }
+
+    /// Content without revision can not be revalidated, so it is not kept.
+    pub fn insert(&self, key: &ParsedContentKey, revision: Option<&str>, config: &ConfigFormat) {
+        let mut entries = self.entries.lock().unwrap();
+        match revision {
+            Some(revision) => {
+                entries.insert(
+                    key.clone(),
+                    ParsedContent {
+                        revision: revision.to_string(),
+                        config: config.clone(),
+                    },
+                );
+            }
+            None => {
+                entries.remove(key);
+            }
+        }
+    }
+
+    /// Drops documents parsed from responses of deleted store.
+    pub fn evict(&self, store_uid: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|key| key.store_uid != store_uid);
+    }
+}
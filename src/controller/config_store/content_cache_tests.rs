@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use crate::controller::config_store::content_cache::{
+        BoundedMap, ParsedContentCache, ParsedContentKey,
+    };
+    use crate::controller::utils::file_format::ConfigFormat;
+    use serde_json::json;
+
+    fn key(store_uid: &str) -> ParsedContentKey {
+        ParsedContentKey {
+            store_uid: store_uid.to_string(),
+            generation: Some(1),
+            request: String::from("default/ConfigurationStore/store"),
+        }
+    }
+
+    fn document(
+        cache: &ParsedContentCache,
+        key: &ParsedContentKey,
+        revision: Option<&str>,
+    ) -> Option<serde_json::Value> {
+        match cache.get(key, revision) {
+            Some(ConfigFormat::Json(json)) => Some(json),
+            Some(_) => panic!("Expected JSON document"),
+            None => None,
+        }
+    }
+
+    #[test]
+    fn test_parsed_document_is_reused_for_same_revision_only() {
+        let cache = ParsedContentCache::default();
+        let key = key("uid");
+        cache.insert(&key, Some("\"v1\""), &ConfigFormat::Json(json!({ "a": 1 })));
+
+        assert_eq!(
+            document(&cache, &key, Some("\"v1\"")),
+            Some(json!({ "a": 1 }))
+        );
+        assert_eq!(document(&cache, &key, Some("\"v2\"")), None);
+        assert_eq!(document(&cache, &key, None), None);
+
+        // Content without revision replaces cached one
+        cache.insert(&key, None, &ConfigFormat::Json(json!({ "a": 2 })));
+        assert_eq!(document(&cache, &key, Some("\"v1\"")), None);
+    }
+
+    #[test]
+    fn test_documents_of_deleted_store_are_evicted() {
+        let cache = ParsedContentCache::default();
+        let config = ConfigFormat::Json(json!({ "a": 1 }));
+        cache.insert(&key("deleted"), Some("v1"), &config);
+        cache.insert(&key("kept"), Some("v1"), &config);
+
+        cache.evict("deleted");
+
+        assert_eq!(document(&cache, &key("deleted"), Some("v1")), None);
+        assert_eq!(
+            document(&cache, &key("kept"), Some("v1")),
+            Some(json!({ "a": 1 }))
+        );
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let cache = ParsedContentCache::with_capacity(2);
+        let config = ConfigFormat::Json(json!({ "a": 1 }));
+        cache.insert(&key("first"), Some("v1"), &config);
+        cache.insert(&key("second"), Some("v1"), &config);
+        // Replacing existing entry does not evict another one
+        cache.insert(&key("first"), Some("v2"), &config);
+        assert!(document(&cache, &key("second"), Some("v1")).is_some());
+
+        cache.insert(&key("third"), Some("v1"), &config);
+
+        assert_eq!(document(&cache, &key("second"), Some("v1")), None);
+        assert!(document(&cache, &key("first"), Some("v2")).is_some());
+        assert!(document(&cache, &key("third"), Some("v1")).is_some());
+    }
+
+    #[test]
+    fn test_bounded_map_drops_oldest_entry() {
+        let mut map = BoundedMap::new(2);
+        map.insert(String::from("a"), 1);
+        map.insert(String::from("b"), 2);
+        map.insert(String::from("c"), 3);
+
+        assert_eq!(map.get("a"), None);
+        assert_eq!(map.get("b"), Some(&2));
+        assert_eq!(map.get("c"), Some(&3));
+    }
+}
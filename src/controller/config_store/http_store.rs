@@ -1,59 +1,255 @@
-use crate::contract::iconfigstore::IConfigStore;
+use crate::contract::iconfigstore::{ConfigPayload, IConfigStore};
 use crate::contract::lib::Error;
+use crate::controller::config_store::content_cache::BoundedMap;
+use crate::controller::config_store::retry::{parse_retry_after, with_retries, RetryOptions};
 use async_trait::async_trait;
 use log::debug;
+use once_cell::sync::OnceCell;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
 use reqwest::header;
 use reqwest::header::HeaderMap;
-use std::collections::HashMap;
-use std::hash::Hash;
-use std::os::linux::raw::stat;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tracing_subscriber::fmt::format;
 
 pub struct HttpConfigStoreConnectionDetails {
     pub base_url: String,
     pub path: Option<String>,
+    pub health_path: Option<String>,
     pub protocol: Option<String>,
     pub headers: HashMap<String, String>,
     pub query_params: HashMap<String, String>,
+    pub authorization: Option<HttpAuthorization>,
+    pub client_identity: Option<HttpClientIdentity>,
+    pub tls: HttpTlsOptions,
+    pub connect_timeout: Duration,
+    pub request_timeout: Duration,
+    pub retry: RetryOptions,
+}
+
+impl Hash for HttpConfigStoreConnectionDetails {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.base_url.hash(state);
+        self.path.hash(state);
+        self.health_path.hash(state);
+        self.protocol.hash(state);
+        self.headers.iter().collect::<BTreeMap<_, _>>().hash(state);
+        self.query_params
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .hash(state);
+        self.authorization.hash(state);
+        self.client_identity.hash(state);
+        self.tls.hash(state);
+        self.connect_timeout.hash(state);
+        self.request_timeout.hash(state);
+        self.retry.hash(state);
+    }
+}
+
+/// Credentials resolved from Secrets, sent with `Authorization` header.
+#[derive(Hash)]
+pub enum HttpAuthorization {
+    Bearer(String),
+    Basic { username: String, password: String },
+}
+
+/// PEM encoded client certificate chain and private key used for mTLS.
+#[derive(Hash)]
+pub struct HttpClientIdentity {
+    pub certificate: String,
+    pub private_key: String,
+}
+
+/// Lowest TLS protocol version accepted from the server.
+#[derive(Debug, Clone, Copy, PartialEq, Hash, Deserialize, Serialize, JsonSchema)]
+pub enum TlsVersion {
+    #[serde(rename = "1.2")]
+    Tls12,
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+impl From<TlsVersion> for reqwest::tls::Version {
+    fn from(version: TlsVersion) -> Self {
+        match version {
+            TlsVersion::Tls12 => reqwest::tls::Version::TLS_1_2,
+            TlsVersion::Tls13 => reqwest::tls::Version::TLS_1_3,
+        }
+    }
+}
+
+#[derive(Default, Hash)]
+pub struct HttpTlsOptions {
+    /// PEM encoded CA certificates trusted in addition to built in roots
+    pub ca_bundle: Option<String>,
+    /// Name used for SNI and certificate verification instead of `base_url` host
+    pub server_name: Option<String>,
+    pub min_version: Option<TlsVersion>,
+}
+
+/// Resolves overridden server name to the address of the host from `base_url`, so connection
+/// still goes to that host while TLS handshake is done for the overridden name.
+struct ServerNameResolver {
+    server_name: String,
+    host: String,
+}
+
+impl Resolve for ServerNameResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = if name.as_str().eq_ignore_ascii_case(&self.server_name) {
+            self.host.clone()
+        } else {
+            name.as_str().to_string()
+        };
+        Box::pin(async move {
+            // Port is replaced with the one from request url by connector
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// `ETag` and `Last-Modified` of response, sent back in conditional request.
+#[derive(Clone, Default)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let value = |name| {
+            headers
+                .get(name)
+                .and_then(|value: &header::HeaderValue| value.to_str().ok())
+                .map(String::from)
+        };
+        Validators {
+            etag: value(header::ETAG),
+            last_modified: value(header::LAST_MODIFIED),
+        }
+    }
+
+    fn revision(&self) -> Option<String> {
+        self.etag.clone().or_else(|| self.last_modified.clone())
+    }
+
+    fn apply(&self, headers: &mut HeaderMap) {
+        let conditions = [
+            (header::IF_NONE_MATCH, &self.etag),
+            (header::IF_MODIFIED_SINCE, &self.last_modified),
+        ];
+        for (name, value) in conditions {
+            if let Some(value) = value.as_ref().and_then(|v| v.parse().ok()) {
+                headers.insert(name, value);
+            }
+        }
+    }
+}
+
+/// Responses kept per store. Query params come from claims, so each distinct set of them
+/// would otherwise be cached for the lifetime of the store.
+pub const MAX_CACHED_RESPONSES: usize = 256;
+
+struct CachedResponse {
+    validators: Validators,
+    payload: ConfigPayload,
 }
 
 pub struct HttpConfigStore {
     config: HttpConfigStoreConnectionDetails,
+    /// Built on first fetch and reused afterwards, so connections are pooled between fetches
+    client: OnceCell<reqwest::Client>,
+    /// Last response per request url, i.e. per query params, revalidated with conditional request
+    cache: Mutex<BoundedMap<String, CachedResponse>>,
 }
 
 impl HttpConfigStore {
     pub fn new(config: HttpConfigStoreConnectionDetails) -> Self {
-        HttpConfigStore { config }
+        HttpConfigStore {
+            config,
+            client: OnceCell::new(),
+            cache: Mutex::new(BoundedMap::new(MAX_CACHED_RESPONSES)),
+        }
     }
-}
 
-#[async_trait]
-impl IConfigStore for HttpConfigStore {
-    async fn get_config(
-        &self,
-        query_params: Option<HashMap<String, String>>,
-        headers: Option<HashMap<String, String>>,
-    ) -> Result<String, Error> {
+    fn build_client(&self, url: &reqwest::Url) -> Result<reqwest::Client, Error> {
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(self.config.connect_timeout)
+            .timeout(self.config.request_timeout);
+        let tls = &self.config.tls;
+
+        if let Some(ca_bundle) = &tls.ca_bundle {
+            let certificates = reqwest::Certificate::from_pem_bundle(ca_bundle.as_bytes())
+                .map_err(Error::HttpConfigStoreError)?;
+            if certificates.is_empty() {
+                return Err(Error::InvalidConfigStore(String::from(
+                    "CA bundle does not contain any PEM certificate",
+                )));
+            }
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if let Some(min_version) = tls.min_version {
+            builder = builder.min_tls_version(min_version.into());
+        }
+
+        if let (Some(server_name), Some(host)) = (&tls.server_name, url.host_str()) {
+            builder = builder.dns_resolver(Arc::new(ServerNameResolver {
+                server_name: server_name.clone(),
+                host: host.trim_matches(|c| c == '[' || c == ']').to_string(),
+            }));
+        }
+
+        if let Some(identity) = &self.config.client_identity {
+            let pem = format!("{}\n{}", identity.private_key, identity.certificate);
+            let identity =
+                reqwest::Identity::from_pem(pem.as_bytes()).map_err(Error::HttpConfigStoreError)?;
+            builder = builder.identity(identity);
+        }
+
+        builder.build().map_err(Error::HttpConfigStoreError)
+    }
+
+    fn request_headers(&self, headers: HashMap<String, String>) -> Result<HeaderMap, Error> {
         let mut merged_headers_map: HashMap<String, String> = HashMap::new();
         merged_headers_map.extend(self.config.headers.clone());
-        merged_headers_map.extend(headers.unwrap_or(HashMap::new()));
-
-        let mut merged_query_params: HashMap<String, String> = HashMap::new();
-        merged_query_params.extend(self.config.query_params.clone());
-        merged_query_params.extend(query_params.unwrap_or(HashMap::new()));
+        merged_headers_map.extend(headers);
 
-        let headers: HeaderMap = (&merged_headers_map).try_into().expect("Valid headers");
+        if self.config.authorization.is_some() {
+            // Credentials from Secret take precedence over plain header
+            merged_headers_map
+                .retain(|k, _| !k.eq_ignore_ascii_case(header::AUTHORIZATION.as_str()));
+        }
 
-        let client = reqwest::Client::new();
+        (&merged_headers_map).try_into().map_err(|_| {
+            Error::HttpConfigStoreClientError(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Invalid headers",
+            ))
+        })
+    }
 
+    /// Builds request url for given path, together with client able to reach it.
+    fn request_url(
+        &self,
+        path: &str,
+        query_params: HashMap<String, String>,
+    ) -> Result<(&reqwest::Client, reqwest::Url), Error> {
         let protocol = &self.config.protocol.clone().unwrap_or(String::from("http"));
-        let path = &self.config.path.clone().unwrap_or(String::from(""));
         let base_url = &self.config.base_url.clone();
 
         let processed_url = format!("{}://{}{}", protocol, base_url, path,);
 
-        let url = reqwest::Url::parse_with_params(processed_url.as_str(), merged_query_params)
+        let mut url = reqwest::Url::parse_with_params(processed_url.as_str(), query_params)
             .map_err(|e| {
                 Error::HttpConfigStoreClientError(std::io::Error::new(
                     std::io::ErrorKind::Other,
@@ -61,28 +257,69 @@ impl IConfigStore for HttpConfigStore {
                 ))
             })?;
 
-        let response = client
-            .get(url.clone())
-            .headers(headers)
-            .timeout(Duration::from_secs(5))
+        let client = self.client.get_or_try_init(|| self.build_client(&url))?;
+
+        if let Some(server_name) = &self.config.tls.server_name {
+            // Url host drives both SNI and certificate verification, resolver maps it back
+            url.set_host(Some(server_name)).map_err(|e| {
+                Error::InvalidConfigStore(format!("Invalid server name {}: {}", server_name, e))
+            })?;
+        }
+
+        Ok((client, url))
+    }
+
+    async fn fetch(
+        &self,
+        client: &reqwest::Client,
+        url: &reqwest::Url,
+        headers: &HeaderMap,
+    ) -> Result<(ConfigPayload, Validators), Error> {
+        let request = client.get(url.clone()).headers(headers.clone());
+        let request = match &self.config.authorization {
+            Some(HttpAuthorization::Bearer(token)) => request.bearer_auth(token),
+            Some(HttpAuthorization::Basic { username, password }) => {
+                request.basic_auth(username, Some(password))
+            }
+            None => request,
+        };
+
+        let response = request
             .send()
             .await
             .map_err(|e| Error::HttpConfigStoreError(e))?; // Explicitly convert error
 
         let status_code = response.status();
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+        let validators = Validators::from_headers(response.headers());
 
-        let res_txt = response
-            .text()
+        let body = response
+            .bytes()
             .await
             .map_err(|e| Error::HttpConfigStoreError(e))?;
 
         if !status_code.is_success() {
+            let res_txt = String::from_utf8_lossy(&body).to_string();
             debug!(
                 "Extractor responded with error Url: {}, Response: {}, StatusCode: {}",
                 &url.as_str(),
                 res_txt,
                 status_code.as_str()
             );
+            if status_code == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                if let Some(retry_after) = retry_after {
+                    return Err(Error::ConfigStoreRateLimited(retry_after));
+                }
+            }
             // Check if the response status is successful
             if status_code.is_server_error() {
                 return Err(Error::HttpConfigStoreServerError(std::io::Error::new(
@@ -98,6 +335,84 @@ impl IConfigStore for HttpConfigStore {
             }
         }
 
-        Ok(res_txt)
+        let payload = ConfigPayload {
+            body: body.to_vec(),
+            content_type,
+            revision: validators.revision(),
+            not_modified: status_code == reqwest::StatusCode::NOT_MODIFIED,
+        };
+        Ok((payload, validators))
+    }
+}
+
+#[async_trait]
+impl IConfigStore for HttpConfigStore {
+    async fn get_config(
+        &self,
+        query_params: Option<HashMap<String, String>>,
+        headers: Option<HashMap<String, String>>,
+    ) -> Result<String, Error> {
+        self.get_payload(query_params, headers)
+            .await
+            .map(|payload| String::from_utf8_lossy(&payload.body).to_string())
+    }
+
+    async fn get_payload(
+        &self,
+        query_params: Option<HashMap<String, String>>,
+        headers: Option<HashMap<String, String>>,
+    ) -> Result<ConfigPayload, Error> {
+        let mut merged_query_params: HashMap<String, String> = HashMap::new();
+        merged_query_params.extend(self.config.query_params.clone());
+        merged_query_params.extend(query_params.unwrap_or(HashMap::new()));
+
+        let mut headers = self.request_headers(headers.unwrap_or(HashMap::new()))?;
+        let path = &self.config.path.clone().unwrap_or(String::from(""));
+        let (client, url) = self.request_url(path, merged_query_params)?;
+
+        if let Some(cached) = self.cache.lock().unwrap().get(url.as_str()) {
+            cached.validators.apply(&mut headers);
+        }
+        let (payload, validators) =
+            with_retries(&self.config.retry, || self.fetch(client, &url, &headers)).await?;
+
+        let mut cache = self.cache.lock().unwrap();
+        if payload.not_modified {
+            return cache
+                .get(url.as_str())
+                .map(|cached| ConfigPayload {
+                    not_modified: true,
+                    ..cached.payload.clone()
+                })
+                .ok_or_else(|| {
+                    Error::InvalidConfigStore(String::from(
+                        "Store responded Not Modified to unconditional request",
+                    ))
+                });
+        }
+        if payload.revision.is_some() {
+            cache.insert(
+                url.to_string(),
+                CachedResponse {
+                    validators,
+                    payload: payload.clone(),
+                },
+            );
+        } else {
+            cache.remove(url.as_str());
+        }
+        Ok(payload)
+    }
+
+    /// Probes `health_path` when configured, without retries so probe reflects current state.
+    async fn health_check(&self) -> Result<(), Error> {
+        let Some(health_path) = &self.config.health_path else {
+            return Ok(());
+        };
+
+        let headers = self.request_headers(HashMap::new())?;
+        let (client, url) = self.request_url(health_path, HashMap::new())?;
+
+        self.fetch(client, &url, &headers).await.map(|_| ())
     }
 }
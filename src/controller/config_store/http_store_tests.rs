@@ -0,0 +1,594 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::iconfigstore::IConfigStore;
+    use crate::contract::lib::Error;
+    use crate::controller::config_store::http_store::{
+        HttpAuthorization, HttpClientIdentity, HttpConfigStore, HttpConfigStoreConnectionDetails,
+        HttpTlsOptions, TlsVersion,
+    };
+    use crate::controller::config_store::retry::RetryOptions;
+    use rustls::server::WebPkiClientVerifier;
+    use rustls::{RootCertStore, ServerConfig, SupportedProtocolVersion};
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+    use wiremock::matchers::{header, header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn http_store(
+        server: &MockServer,
+        headers: HashMap<String, String>,
+        authorization: Option<HttpAuthorization>,
+    ) -> HttpConfigStore {
+        HttpConfigStore::new(HttpConfigStoreConnectionDetails {
+            base_url: server.address().to_string(),
+            path: Some(String::from("/config")),
+            health_path: None,
+            protocol: None,
+            headers,
+            query_params: HashMap::new(),
+            authorization,
+            client_identity: None,
+            tls: HttpTlsOptions::default(),
+            connect_timeout: Duration::from_secs(5),
+            request_timeout: Duration::from_secs(5),
+            retry: fast_retries(),
+        })
+    }
+
+    fn probed_http_store(server: &MockServer, health_path: Option<&str>) -> HttpConfigStore {
+        HttpConfigStore::new(HttpConfigStoreConnectionDetails {
+            base_url: server.address().to_string(),
+            path: Some(String::from("/config")),
+            health_path: health_path.map(String::from),
+            protocol: None,
+            headers: HashMap::new(),
+            query_params: HashMap::new(),
+            authorization: None,
+            client_identity: None,
+            tls: HttpTlsOptions::default(),
+            connect_timeout: Duration::from_secs(5),
+            request_timeout: Duration::from_secs(5),
+            retry: fast_retries(),
+        })
+    }
+
+    fn fast_retries() -> RetryOptions {
+        RetryOptions {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(50),
+        }
+    }
+
+    static CA_CERT: &str = include_str!("testdata/ca.crt");
+    static SERVER_CERT: &str = include_str!("testdata/server.crt");
+    static SERVER_KEY: &str = include_str!("testdata/server.key");
+    static CLIENT_CERT: &str = include_str!("testdata/client.crt");
+    static CLIENT_KEY: &str = include_str!("testdata/client.key");
+    static SERVER_NAME: &str = "config.internal";
+
+    /// Starts HTTPS server presenting certificate for `config.internal` issued by test CA.
+    /// Optionally requires client certificate issued by the same CA.
+    async fn start_tls_server(
+        versions: &[&'static SupportedProtocolVersion],
+        require_client_certificate: bool,
+    ) -> SocketAddr {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let certificates = rustls_pemfile::certs(&mut SERVER_CERT.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let key = rustls_pemfile::private_key(&mut SERVER_KEY.as_bytes())
+            .unwrap()
+            .unwrap();
+
+        let builder = ServerConfig::builder_with_provider(provider.clone())
+            .with_protocol_versions(versions)
+            .unwrap();
+        let builder = if require_client_certificate {
+            let mut roots = RootCertStore::empty();
+            for certificate in rustls_pemfile::certs(&mut CA_CERT.as_bytes()) {
+                roots.add(certificate.unwrap()).unwrap();
+            }
+            builder.with_client_cert_verifier(
+                WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                    .build()
+                    .unwrap(),
+            )
+        } else {
+            builder.with_no_client_auth()
+        };
+        let acceptor = TlsAcceptor::from(Arc::new(
+            builder.with_single_cert(certificates, key).unwrap(),
+        ));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    // Failed handshakes are expected in negative tests
+                    let Ok(mut stream) = acceptor.accept(stream).await else {
+                        return;
+                    };
+                    let mut request = Vec::new();
+                    let mut buffer = [0u8; 1024];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        match stream.read(&mut buffer).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buffer[..n]),
+                        }
+                    }
+                    let body = "{\"tls\": true}";
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+        address
+    }
+
+    fn https_store(
+        address: SocketAddr,
+        tls: HttpTlsOptions,
+        client_identity: Option<HttpClientIdentity>,
+    ) -> HttpConfigStore {
+        HttpConfigStore::new(HttpConfigStoreConnectionDetails {
+            base_url: address.to_string(),
+            path: Some(String::from("/config")),
+            health_path: None,
+            protocol: Some(String::from("https")),
+            headers: HashMap::new(),
+            query_params: HashMap::new(),
+            authorization: None,
+            client_identity,
+            tls,
+            connect_timeout: Duration::from_secs(5),
+            request_timeout: Duration::from_secs(5),
+            retry: fast_retries(),
+        })
+    }
+
+    fn trusted_tls(min_version: Option<TlsVersion>) -> HttpTlsOptions {
+        HttpTlsOptions {
+            ca_bundle: Some(CA_CERT.to_string()),
+            server_name: Some(SERVER_NAME.to_string()),
+            min_version,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_private_ca_bundle_and_server_name_are_used_for_verification() {
+        let address = start_tls_server(rustls::ALL_VERSIONS, false).await;
+
+        let config = https_store(address, trusted_tls(None), None)
+            .get_config(None, None)
+            .await
+            .expect("Config should be returned over TLS");
+
+        assert_eq!(config, "{\"tls\": true}");
+    }
+
+    #[tokio::test]
+    async fn test_certificate_from_unknown_ca_is_rejected() {
+        let address = start_tls_server(rustls::ALL_VERSIONS, false).await;
+
+        let tls = HttpTlsOptions {
+            ca_bundle: None,
+            ..trusted_tls(None)
+        };
+        match https_store(address, tls, None).get_config(None, None).await {
+            Err(Error::HttpConfigStoreError(_)) => {}
+            _ => panic!("Expected Error::HttpConfigStoreError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_below_min_tls_version_is_rejected() {
+        let address = start_tls_server(&[&rustls::version::TLS12], false).await;
+
+        let store = https_store(address, trusted_tls(Some(TlsVersion::Tls12)), None);
+        store
+            .get_config(None, None)
+            .await
+            .expect("TLS 1.2 should be accepted");
+
+        let store = https_store(address, trusted_tls(Some(TlsVersion::Tls13)), None);
+        match store.get_config(None, None).await {
+            Err(Error::HttpConfigStoreError(_)) => {}
+            _ => panic!("Expected Error::HttpConfigStoreError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_certificate_is_presented_for_mtls() {
+        let address = start_tls_server(rustls::ALL_VERSIONS, true).await;
+
+        let identity = HttpClientIdentity {
+            certificate: CLIENT_CERT.to_string(),
+            private_key: CLIENT_KEY.to_string(),
+        };
+        https_store(address, trusted_tls(None), Some(identity))
+            .get_config(None, None)
+            .await
+            .expect("Config should be returned with client certificate");
+
+        assert!(https_store(address, trusted_tls(None), None)
+            .get_config(None, None)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ca_bundle_without_certificates_is_rejected() {
+        let tls = HttpTlsOptions {
+            ca_bundle: Some(String::from("not a certificate")),
+            ..HttpTlsOptions::default()
+        };
+
+        match https_store(SocketAddr::from(([127, 0, 0, 1], 1)), tls, None)
+            .get_config(None, None)
+            .await
+        {
+            Err(Error::InvalidConfigStore(_)) => {}
+            _ => panic!("Expected Error::InvalidConfigStore"),
+        }
+    }
+
+    async fn mount_config_for_authorization(server: &MockServer, authorization: &str) {
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .and(header("Authorization", authorization))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"asd\": 1}"))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_bearer_token_is_sent_in_authorization_header() {
+        let server = MockServer::start().await;
+        mount_config_for_authorization(&server, "Bearer s3cr3t").await;
+
+        let config = http_store(
+            &server,
+            HashMap::new(),
+            Some(HttpAuthorization::Bearer(String::from("s3cr3t"))),
+        )
+        .get_config(None, None)
+        .await
+        .expect("Config should be returned");
+
+        assert_eq!(config, "{\"asd\": 1}");
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_credentials_are_sent_in_authorization_header() {
+        let server = MockServer::start().await;
+        // base64("user:pass")
+        mount_config_for_authorization(&server, "Basic dXNlcjpwYXNz").await;
+
+        let config = http_store(
+            &server,
+            HashMap::new(),
+            Some(HttpAuthorization::Basic {
+                username: String::from("user"),
+                password: String::from("pass"),
+            }),
+        )
+        .get_config(None, None)
+        .await
+        .expect("Config should be returned");
+
+        assert_eq!(config, "{\"asd\": 1}");
+    }
+
+    #[tokio::test]
+    async fn test_secret_credentials_override_plain_authorization_header() {
+        let server = MockServer::start().await;
+        mount_config_for_authorization(&server, "Bearer from-secret").await;
+
+        let mut headers = HashMap::new();
+        headers.insert(
+            String::from("authorization"),
+            String::from("Bearer pasted-into-spec"),
+        );
+
+        let config = http_store(
+            &server,
+            headers,
+            Some(HttpAuthorization::Bearer(String::from("from-secret"))),
+        )
+        .get_config(None, None)
+        .await
+        .expect("Config should be returned");
+
+        assert_eq!(config, "{\"asd\": 1}");
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_are_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"asd\": 1}"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = http_store(&server, HashMap::new(), None)
+            .get_config(None, None)
+            .await
+            .expect("Config should be returned after retries");
+
+        assert_eq!(config, "{\"asd\": 1}");
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_requests_are_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(1)))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"asd\": 1}"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let store = HttpConfigStore::new(HttpConfigStoreConnectionDetails {
+            base_url: server.address().to_string(),
+            path: Some(String::from("/config")),
+            health_path: None,
+            protocol: None,
+            headers: HashMap::new(),
+            query_params: HashMap::new(),
+            authorization: None,
+            client_identity: None,
+            tls: HttpTlsOptions::default(),
+            connect_timeout: Duration::from_secs(5),
+            request_timeout: Duration::from_millis(100),
+            retry: fast_retries(),
+        });
+
+        let config = store
+            .get_config(None, None)
+            .await
+            .expect("Config should be returned after timed out attempts");
+
+        assert_eq!(config, "{\"asd\": 1}");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_header_is_rejected_as_client_error() {
+        let server = MockServer::start().await;
+        let headers = HashMap::from([(String::from("X-Bad"), String::from("line\nbreak"))]);
+
+        match http_store(&server, headers, None)
+            .get_config(None, None)
+            .await
+        {
+            Err(Error::HttpConfigStoreClientError(_)) => {}
+            _ => panic!("Expected Error::HttpConfigStoreClientError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_are_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        match http_store(&server, HashMap::new(), None)
+            .get_config(None, None)
+            .await
+        {
+            Err(Error::HttpConfigStoreClientError(_)) => {}
+            _ => panic!("Expected Error::HttpConfigStoreClientError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_request_is_retried_only_with_retry_after() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .and(header("X-Retry-After", "yes"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .and(header("X-Retry-After", "yes"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"asd\": 1}"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .and(header("X-Retry-After", "no"))
+            .respond_with(ResponseTemplate::new(429))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let store = http_store(&server, HashMap::new(), None);
+        let with_retry_after =
+            HashMap::from([(String::from("X-Retry-After"), String::from("yes"))]);
+        store
+            .get_config(None, Some(with_retry_after))
+            .await
+            .expect("Config should be returned after Retry-After");
+
+        let without_retry_after =
+            HashMap::from([(String::from("X-Retry-After"), String::from("no"))]);
+        match store.get_config(None, Some(without_retry_after)).await {
+            Err(Error::HttpConfigStoreClientError(_)) => {}
+            _ => panic!("Expected Error::HttpConfigStoreClientError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_path_is_probed_without_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/healthz"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/unhealthy"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        probed_http_store(&server, Some("/healthz"))
+            .health_check()
+            .await
+            .expect("Store should be healthy");
+        match probed_http_store(&server, Some("/unhealthy"))
+            .health_check()
+            .await
+        {
+            Err(Error::HttpConfigStoreServerError(_)) => {}
+            _ => panic!("Expected Error::HttpConfigStoreServerError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_without_health_path_is_not_probed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        probed_http_store(&server, None)
+            .health_check()
+            .await
+            .expect("Store without health path should be considered healthy");
+    }
+
+    #[tokio::test]
+    async fn test_binary_payload_is_returned_untouched() {
+        let server = MockServer::start().await;
+        let keystore = vec![0x30, 0x82, 0xff, 0x00, 0xfe];
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(keystore.clone(), "application/x-pkcs12"),
+            )
+            .mount(&server)
+            .await;
+
+        let payload = http_store(&server, HashMap::new(), None)
+            .get_payload(None, None)
+            .await
+            .expect("Payload should be returned");
+
+        assert_eq!(payload.body, keystore);
+        assert_eq!(
+            payload.content_type.as_deref(),
+            Some("application/x-pkcs12")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_content_is_revalidated_with_etag() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .and(header("If-None-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304).insert_header("ETag", "\"v1\""))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_raw("{\"a\": 1}", "application/json"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let store = http_store(&server, HashMap::new(), None);
+
+        let downloaded = store.get_payload(None, None).await.unwrap();
+        assert!(!downloaded.not_modified);
+        assert_eq!(downloaded.revision.as_deref(), Some("\"v1\""));
+
+        let revalidated = store.get_payload(None, None).await.unwrap();
+        assert!(revalidated.not_modified);
+        assert_eq!(revalidated.body, downloaded.body);
+        assert_eq!(
+            revalidated.content_type.as_deref(),
+            Some("application/json")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_last_modified_is_sent_per_query_params() {
+        let server = MockServer::start().await;
+        let last_modified = "Wed, 21 Oct 2015 07:28:00 GMT";
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .and(header_exists("If-Modified-Since"))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Last-Modified", last_modified)
+                    .set_body_string("a: 1"),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        let store = http_store(&server, HashMap::new(), None);
+        let params = |env: &str| Some(HashMap::from([(String::from("env"), env.to_string())]));
+
+        store.get_payload(params("dev"), None).await.unwrap();
+        // Other params are different document, fetched unconditionally
+        let other = store.get_payload(params("prod"), None).await.unwrap();
+        assert!(!other.not_modified);
+
+        let revalidated = store.get_payload(params("dev"), None).await.unwrap();
+        assert!(revalidated.not_modified);
+        assert_eq!(revalidated.body, b"a: 1");
+    }
+}
@@ -1,2 +1,13 @@
+pub mod circuit_breaker;
+mod circuit_breaker_tests;
+pub mod content_cache;
+mod content_cache_tests;
 pub mod http_store;
+mod http_store_tests;
+pub mod retry;
+mod retry_tests;
+pub mod store_registry;
+mod store_registry_tests;
+pub mod vault_auth;
 pub mod vault_store;
+mod vault_store_tests;
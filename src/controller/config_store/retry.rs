@@ -0,0 +1,108 @@
+use crate::contract::lib::Error;
+use log::debug;
+use rand::Rng;
+use std::future::Future;
+use std::time::Duration;
+
+pub static DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+pub static DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct RetryOptions {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryOptions {
+    fn default() -> Self {
+        RetryOptions {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryOptions {
+    /// Exponential backoff capped at `max_backoff`, with full jitter so that claims failing
+    /// at the same time do not hit recovering store at once.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff);
+        exponential.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+    }
+
+    /// Returns how long to wait before retrying failed attempt, or `None` when error should
+    /// be returned right away.
+    fn retry_delay(&self, error: &Error, attempt: u32) -> Option<Duration> {
+        match error {
+            // Waiting longer than backoff allows would hold reconcile, let it requeue instead
+            Error::ConfigStoreRateLimited(retry_after) => {
+                Some(*retry_after).filter(|retry_after| *retry_after <= self.max_backoff)
+            }
+            e if is_transient(e) => Some(self.backoff(attempt)),
+            _ => None,
+        }
+    }
+}
+
+/// Whether error means the store is unavailable right now, rather than rejecting the request:
+/// server errors, connection errors, timeouts and rate limiting.
+pub fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::HttpConfigStoreServerError(_)
+        | Error::VaultConfigStoreServerError(_)
+        | Error::ConfigStoreRateLimited(_) => true,
+        Error::HttpConfigStoreError(e) | Error::VaultConfigStoreError(e) => {
+            e.is_connect() || e.is_timeout()
+        }
+        _ => false,
+    }
+}
+
+/// Runs `operation` until it succeeds, fails with non retryable error or retries run out.
+/// Retried are server errors, connection errors, timeouts and rate limited responses with `Retry-After`.
+pub async fn with_retries<T, F, Fut>(options: &RetryOptions, mut operation: F) -> Result<T, Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let mut attempt = 0;
+    loop {
+        match operation().await {
+            Err(e) if attempt < options.max_retries => match options.retry_delay(&e, attempt) {
+                Some(delay) => {
+                    debug!(
+                        "Config store request failed, retrying in {:?} ({}/{}): {}",
+                        delay,
+                        attempt + 1,
+                        options.max_retries,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                None => return Err(e),
+            },
+            result => return result,
+        }
+    }
+}
+
+/// Parses `Retry-After` header given either as delay in seconds or as HTTP date.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    chrono::DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|date| {
+            (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                .to_std()
+                .unwrap_or_default()
+        })
+}
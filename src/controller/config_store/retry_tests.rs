@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::lib::Error;
+    use crate::controller::config_store::retry::{parse_retry_after, with_retries, RetryOptions};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    fn fast_retries(max_retries: u32) -> RetryOptions {
+        RetryOptions {
+            max_retries,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(10),
+        }
+    }
+
+    async fn count_attempts(options: &RetryOptions, error: fn() -> Error) -> u32 {
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), Error> = with_retries(options, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(error())
+        })
+        .await;
+        assert!(result.is_err());
+        attempts.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_are_retried_until_retries_run_out() {
+        let attempts = count_attempts(&fast_retries(3), || {
+            Error::HttpConfigStoreServerError(std::io::Error::other("unavailable"))
+        })
+        .await;
+
+        assert_eq!(attempts, 4);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_fail_fast() {
+        let attempts = count_attempts(&fast_retries(3), || {
+            Error::HttpConfigStoreClientError(std::io::Error::other("not found"))
+        })
+        .await;
+
+        assert_eq!(attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_longer_than_max_backoff_is_not_waited_for() {
+        let attempts = count_attempts(&fast_retries(3), || {
+            Error::ConfigStoreRateLimited(Duration::from_secs(60))
+        })
+        .await;
+
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn test_retry_after_is_parsed_from_seconds_and_http_date() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
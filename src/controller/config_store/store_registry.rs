@@ -0,0 +1,71 @@
+use crate::contract::iconfigstore::IConfigStore;
+use crate::controller::config_store::circuit_breaker::{
+    CircuitBreakerOptions, CircuitBreakerStore,
+};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+struct RegisteredStore {
+    generation: Option<i64>,
+    fingerprint: u64,
+    store: Arc<CircuitBreakerStore>,
+}
+
+/// Long-lived config store instances, keyed by UID of ConfigurationStore/ClusterConfigurationStore.
+/// Keeping instances between reconciles lets them reuse connection pools, TLS sessions and tokens,
+/// and lets circuit breaker track failures across all claims using the store.
+#[derive(Default)]
+pub struct ConfigStoreRegistry {
+    stores: Mutex<HashMap<String, RegisteredStore>>,
+}
+
+impl ConfigStoreRegistry {
+    /// Returns registered store instance, or builds new one when store spec was modified or
+    /// its resolved connection details changed, e.g. after credentials in referenced Secret rotated.
+    /// Status writes do not change generation, so they keep the instance with its breaker state.
+    /// Built store is guarded by circuit breaker.
+    pub fn get_or_build<F>(
+        &self,
+        store_uid: &str,
+        generation: Option<i64>,
+        fingerprint: u64,
+        circuit_breaker: &CircuitBreakerOptions,
+        build: F,
+    ) -> Arc<CircuitBreakerStore>
+    where
+        F: FnOnce() -> Arc<dyn IConfigStore>,
+    {
+        let mut stores = self.stores.lock().unwrap();
+
+        if let Some(registered) = stores.get(store_uid) {
+            if registered.generation == generation && registered.fingerprint == fingerprint {
+                return registered.store.clone();
+            }
+        }
+
+        let store = Arc::new(CircuitBreakerStore::new(build(), circuit_breaker.clone()));
+        stores.insert(
+            store_uid.to_string(),
+            RegisteredStore {
+                generation,
+                fingerprint,
+                store: store.clone(),
+            },
+        );
+        store
+    }
+
+    /// Drops instance of deleted store together with its connections and cached credentials.
+    pub fn evict(&self, store_uid: &str) {
+        self.stores.lock().unwrap().remove(store_uid);
+    }
+}
+
+/// Hash of resolved connection details, used to detect changes not reflected in generation.
+pub fn fingerprint<T: Hash>(details: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    details.hash(&mut hasher);
+    hasher.finish()
+}
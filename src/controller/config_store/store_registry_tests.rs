@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::iconfigstore::IConfigStore;
+    use crate::contract::lib::Error;
+    use crate::controller::config_store::circuit_breaker::CircuitBreakerOptions;
+    use crate::controller::config_store::store_registry::ConfigStoreRegistry;
+    use crate::controller::v1alpha1::crd::configuration_store::ConfigurationStore;
+    use async_trait::async_trait;
+    use kube::Resource;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    struct StaticStore(&'static str);
+
+    #[async_trait]
+    impl IConfigStore for StaticStore {
+        async fn get_config(
+            &self,
+            _query_params: Option<HashMap<String, String>>,
+            _headers: Option<HashMap<String, String>>,
+        ) -> Result<String, Error> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    fn static_store(config: &'static str) -> Arc<dyn IConfigStore> {
+        Arc::new(StaticStore(config))
+    }
+
+    #[tokio::test]
+    async fn test_store_instance_is_reused_until_store_changes() {
+        let registry = ConfigStoreRegistry::default();
+        let breaker = CircuitBreakerOptions::default();
+
+        let first = registry.get_or_build("uid-1", Some(1), 1, &breaker, || static_store("first"));
+        let reused =
+            registry.get_or_build("uid-1", Some(1), 1, &breaker, || static_store("second"));
+        assert!(Arc::ptr_eq(&first, &reused));
+
+        let modified =
+            registry.get_or_build("uid-1", Some(2), 1, &breaker, || static_store("third"));
+        assert!(!Arc::ptr_eq(&first, &modified));
+        assert_eq!(modified.get_config(None, None).await.unwrap(), "third");
+    }
+
+    #[tokio::test]
+    async fn test_store_is_rebuilt_when_resolved_details_change() {
+        let registry = ConfigStoreRegistry::default();
+        let breaker = CircuitBreakerOptions::default();
+
+        let first = registry.get_or_build("uid-1", Some(1), 1, &breaker, || static_store("first"));
+        // e.g. credentials rotated in referenced Secret, store resource itself is untouched
+        let rotated =
+            registry.get_or_build("uid-1", Some(1), 2, &breaker, || static_store("rotated"));
+
+        assert!(!Arc::ptr_eq(&first, &rotated));
+        assert_eq!(rotated.get_config(None, None).await.unwrap(), "rotated");
+    }
+
+    #[tokio::test]
+    async fn test_stores_are_kept_per_uid() {
+        let registry = ConfigStoreRegistry::default();
+        let breaker = CircuitBreakerOptions::default();
+
+        let first = registry.get_or_build("uid-1", Some(1), 1, &breaker, || static_store("first"));
+        let other = registry.get_or_build("uid-2", Some(1), 1, &breaker, || static_store("other"));
+
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert!(Arc::ptr_eq(
+            &first,
+            &registry.get_or_build("uid-1", Some(1), 1, &breaker, || static_store("first"))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_status_write_keeps_store_instance() {
+        let registry = ConfigStoreRegistry::default();
+        let breaker = CircuitBreakerOptions::default();
+        let mut store: ConfigurationStore = serde_json::from_value(json!({
+            "apiVersion": "external-config.com/v1alpha1",
+            "kind": "ConfigurationStore",
+            "metadata": {
+                "name": "store",
+                "namespace": "default",
+                "uid": "uid-1",
+                "generation": 1,
+                "resourceVersion": "100"
+            },
+            "spec": { "provider": { "http": { "baseUrl": "http://store" } } }
+        }))
+        .unwrap();
+
+        let first = registry.get_or_build("uid-1", store.meta().generation, 1, &breaker, || {
+            static_store("first")
+        });
+        // Status patch bumps resourceVersion only
+        store.metadata.resource_version = Some("101".to_string());
+        let after_status_write =
+            registry.get_or_build("uid-1", store.meta().generation, 1, &breaker, || {
+                static_store("second")
+            });
+
+        assert!(Arc::ptr_eq(&first, &after_status_write));
+    }
+
+    #[tokio::test]
+    async fn test_evicted_store_is_rebuilt() {
+        let registry = ConfigStoreRegistry::default();
+        let breaker = CircuitBreakerOptions::default();
+
+        let first = registry.get_or_build("uid-1", Some(1), 1, &breaker, || static_store("first"));
+        registry.evict("uid-1");
+        let rebuilt =
+            registry.get_or_build("uid-1", Some(1), 1, &breaker, || static_store("rebuilt"));
+
+        assert!(!Arc::ptr_eq(&first, &rebuilt));
+    }
+}
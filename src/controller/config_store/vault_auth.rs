@@ -0,0 +1,200 @@
+use crate::contract::lib::Error;
+use log::{debug, info};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+pub static VAULT_TOKEN_HEADER: &str = "X-Vault-Token";
+pub static VAULT_DEFAULT_KUBERNETES_MOUNT: &str = "kubernetes";
+pub static VAULT_DEFAULT_APPROLE_MOUNT: &str = "approle";
+pub static SERVICE_ACCOUNT_TOKEN_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/token";
+
+/// Resolved credentials used to obtain Vault token.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub enum VaultAuthMethod {
+    Token(String),
+    Kubernetes {
+        mount_path: String,
+        role: String,
+        jwt_path: String,
+    },
+    AppRole {
+        mount_path: String,
+        role_id: String,
+        secret_id: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct VaultToken {
+    pub client_token: String,
+    renewable: bool,
+    ttl: Duration,
+    issued_at: Instant,
+    method: VaultAuthMethod,
+}
+
+impl VaultToken {
+    /// Tokens without TTL (e.g. static tokens) never expire from our point of view.
+    fn needs_refresh(&self) -> bool {
+        if self.ttl.is_zero() {
+            return false;
+        }
+        // Refresh once two thirds of TTL has passed, so there is still time to re-login on failed renewal
+        self.issued_at.elapsed() >= self.ttl.mul_f64(2.0 / 3.0)
+    }
+}
+
+/// Holder of token for a single store. Shared between fetches, so login happens only when
+/// token is missing, expired or auth configuration changed.
+pub type VaultTokenSlot = Arc<tokio::sync::Mutex<Option<VaultToken>>>;
+
+#[derive(Deserialize)]
+struct VaultAuthResponse {
+    auth: VaultAuthData,
+}
+
+#[derive(Deserialize)]
+struct VaultAuthData {
+    client_token: String,
+    #[serde(default)]
+    lease_duration: u64,
+    #[serde(default)]
+    renewable: bool,
+}
+
+pub struct VaultAuthenticator {
+    server: String,
+    method: VaultAuthMethod,
+    slot: VaultTokenSlot,
+}
+
+impl VaultAuthenticator {
+    pub fn new(server: &str, method: VaultAuthMethod, slot: VaultTokenSlot) -> Self {
+        VaultAuthenticator {
+            server: server.trim_end_matches('/').to_string(),
+            method,
+            slot,
+        }
+    }
+
+    /// Returns cached token, renewing or re-authenticating it when it is about to expire.
+    pub async fn token(&self, client: &Client) -> Result<String, Error> {
+        let mut slot = self.slot.lock().await;
+
+        let current = slot.take().filter(|token| token.method == self.method);
+        let token = match current {
+            Some(token) if !token.needs_refresh() => token,
+            Some(token) if token.renewable => match self.renew(client, &token).await {
+                Ok(renewed) => renewed,
+                Err(e) => {
+                    debug!("Vault token renewal failed, logging in again: {:?}", e);
+                    self.login(client).await?
+                }
+            },
+            _ => self.login(client).await?,
+        };
+
+        let client_token = token.client_token.clone();
+        *slot = Some(token);
+        Ok(client_token)
+    }
+
+    /// Drops cached token, e.g. when Vault rejected it as revoked.
+    pub async fn invalidate(&self) {
+        self.slot.lock().await.take();
+    }
+
+    async fn login(&self, client: &Client) -> Result<VaultToken, Error> {
+        let (mount_path, body) = match &self.method {
+            // Static tokens are used as they are, without TTL tracking
+            VaultAuthMethod::Token(token) => {
+                return Ok(self.to_token(VaultAuthData {
+                    client_token: token.clone(),
+                    lease_duration: 0,
+                    renewable: false,
+                }))
+            }
+            VaultAuthMethod::Kubernetes {
+                mount_path,
+                role,
+                jwt_path,
+            } => {
+                // Projected service account tokens are rotated by kubelet, so always read latest one
+                let jwt = tokio::fs::read_to_string(jwt_path)
+                    .await
+                    .map_err(Error::VaultAuthError)?;
+                (mount_path, json!({ "role": role, "jwt": jwt.trim() }))
+            }
+            VaultAuthMethod::AppRole {
+                mount_path,
+                role_id,
+                secret_id,
+            } => (
+                mount_path,
+                json!({ "role_id": role_id, "secret_id": secret_id }),
+            ),
+        };
+
+        let url = format!(
+            "{}/v1/auth/{}/login",
+            self.server,
+            mount_path.trim_matches('/')
+        );
+        let auth = self.send(client.post(url).json(&body)).await.map_err(|e| {
+            Error::VaultAuthError(std::io::Error::other(format!("Login failed: {}", e)))
+        })?;
+
+        info!("Authenticated to Vault at {}", self.server);
+        Ok(self.to_token(auth))
+    }
+
+    async fn renew(&self, client: &Client, token: &VaultToken) -> Result<VaultToken, Error> {
+        let url = format!("{}/v1/auth/token/renew-self", self.server);
+        let auth = self
+            .send(
+                client
+                    .post(url)
+                    .header(VAULT_TOKEN_HEADER, token.client_token.as_str())
+                    .json(&json!({})),
+            )
+            .await?;
+
+        debug!("Renewed Vault token at {}", self.server);
+        Ok(self.to_token(auth))
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<VaultAuthData, Error> {
+        let response = request.send().await.map_err(Error::VaultConfigStoreError)?;
+        let status_code = response.status();
+        let res_txt = response
+            .text()
+            .await
+            .map_err(Error::VaultConfigStoreError)?;
+
+        if !status_code.is_success() {
+            return Err(Error::VaultAuthError(std::io::Error::other(format!(
+                "{} {}",
+                status_code.as_str(),
+                res_txt
+            ))));
+        }
+
+        serde_json::from_str::<VaultAuthResponse>(&res_txt)
+            .map(|res| res.auth)
+            .map_err(Error::JsonSerializationError)
+    }
+
+    fn to_token(&self, auth: VaultAuthData) -> VaultToken {
+        VaultToken {
+            client_token: auth.client_token,
+            renewable: auth.renewable,
+            ttl: Duration::from_secs(auth.lease_duration),
+            issued_at: Instant::now(),
+            method: self.method.clone(),
+        }
+    }
+}
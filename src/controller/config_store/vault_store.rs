@@ -1,23 +1,223 @@
 use crate::contract::iconfigstore::IConfigStore;
 use crate::contract::lib::Error;
+use crate::controller::config_store::retry::{parse_retry_after, with_retries, RetryOptions};
+use crate::controller::config_store::vault_auth::{
+    VaultAuthMethod, VaultAuthenticator, VaultTokenSlot, VAULT_TOKEN_HEADER,
+};
 use async_trait::async_trait;
-use std::collections::HashMap;
+use log::debug;
+use once_cell::sync::OnceCell;
+use reqwest::header::HeaderMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+pub static VAULT_DEFAULT_MOUNT: &str = "secret";
+
+/// Version of the KV secrets engine mounted at the configured path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Hash, Deserialize, Serialize, JsonSchema)]
+pub enum VaultKvVersion {
+    #[serde(rename = "v1")]
+    V1,
+    #[serde(rename = "v2")]
+    #[default]
+    V2,
+}
 
 pub struct VaultConfigStoreConnectionDetails {
     pub url: String,
+    pub mount: String,
+    pub kv_version: VaultKvVersion,
     pub headers: HashMap<String, String>,
-    pub query_params: HashMap<String, String>,
+    pub auth: Option<VaultAuthMethod>,
+    pub connect_timeout: Duration,
+    pub request_timeout: Duration,
+    pub retry: RetryOptions,
+}
+
+impl Hash for VaultConfigStoreConnectionDetails {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.url.hash(state);
+        self.mount.hash(state);
+        self.kv_version.hash(state);
+        self.headers.iter().collect::<BTreeMap<_, _>>().hash(state);
+        self.auth.hash(state);
+        self.connect_timeout.hash(state);
+        self.request_timeout.hash(state);
+        self.retry.hash(state);
+    }
 }
 
 pub struct VaultConfigStore {
     config: VaultConfigStoreConnectionDetails,
+    authenticator: Option<VaultAuthenticator>,
+    client: OnceCell<reqwest::Client>,
 }
 
 impl VaultConfigStore {
-    pub fn new(config: VaultConfigStoreConnectionDetails) -> Self {
-        VaultConfigStore { config }
+    pub fn new(config: VaultConfigStoreConnectionDetails, token_slot: VaultTokenSlot) -> Self {
+        let authenticator = config
+            .auth
+            .clone()
+            .map(|method| VaultAuthenticator::new(&config.url, method, token_slot));
+        VaultConfigStore {
+            config,
+            authenticator,
+            client: OnceCell::new(),
+        }
+    }
+
+    /// Client is built on first request, so invalid settings fail the fetch instead of the operator.
+    fn client(&self) -> Result<&reqwest::Client, Error> {
+        self.client.get_or_try_init(|| {
+            reqwest::Client::builder()
+                .connect_timeout(self.config.connect_timeout)
+                .timeout(self.config.request_timeout)
+                .build()
+                .map_err(Error::VaultConfigStoreError)
+        })
     }
+
+    /// Builds secret read url out of `mount`, `path` and optional `version` store parameters.
+    fn secret_url(&self, params: &HashMap<String, String>) -> Result<reqwest::Url, Error> {
+        let path = params
+            .get("path")
+            .map(|p| p.trim_matches('/'))
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| Error::MissingConfigStoreParameter(String::from("path")))?;
+        let mount = params
+            .get("mount")
+            .unwrap_or(&self.config.mount)
+            .trim_matches('/');
+        let server = self.config.url.trim_end_matches('/');
+
+        let (processed_url, query) = match self.config.kv_version {
+            VaultKvVersion::V1 => (format!("{}/v1/{}/{}", server, mount, path), vec![]),
+            VaultKvVersion::V2 => (
+                format!("{}/v1/{}/data/{}", server, mount, path),
+                params
+                    .get("version")
+                    .map(|version| vec![("version", version.as_str())])
+                    .unwrap_or_default(),
+            ),
+        };
+
+        reqwest::Url::parse_with_params(processed_url.as_str(), query)
+            .map_err(|e| Error::VaultConfigStoreClientError(std::io::Error::other(e.to_string())))
+    }
+
+    /// Extracts secret key-values from KV response envelope.
+    /// KV v1 keeps them directly under `data`, while KV v2 nests them under `data.data`.
+    fn extract_secret_data(&self, body: Value) -> Result<Value, Error> {
+        let data = match self.config.kv_version {
+            VaultKvVersion::V1 => body.get("data"),
+            VaultKvVersion::V2 => body.get("data").and_then(|data| data.get("data")),
+        };
+
+        match data {
+            Some(data) if data.is_object() => Ok(data.clone()),
+            _ => Err(Error::VaultConfigStoreClientError(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "Secret has no data, it might have been deleted or destroyed",
+            ))),
+        }
+    }
+
+    async fn fetch(
+        &self,
+        url: &reqwest::Url,
+        headers: &HashMap<String, String>,
+    ) -> Result<String, Error> {
+        let client = self.client()?;
+
+        let mut merged_headers_map = headers.clone();
+        if let Some(authenticator) = &self.authenticator {
+            merged_headers_map.insert(
+                VAULT_TOKEN_HEADER.to_string(),
+                authenticator.token(client).await?,
+            );
+        }
+
+        let headers: HeaderMap = (&merged_headers_map).try_into().map_err(|_| {
+            Error::VaultConfigStoreClientError(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Invalid headers",
+            ))
+        })?;
+
+        let response = client
+            .get(url.clone())
+            .headers(headers)
+            .send()
+            .await
+            .map_err(Error::VaultConfigStoreError)?;
+
+        let status_code = response.status();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let res_txt = response
+            .text()
+            .await
+            .map_err(Error::VaultConfigStoreError)?;
+
+        if !status_code.is_success() {
+            debug!(
+                "Vault responded with error Url: {}, Response: {}, StatusCode: {}",
+                &url.as_str(),
+                res_txt,
+                status_code.as_str()
+            );
+            let message = vault_error_message(&res_txt);
+            if status_code == reqwest::StatusCode::FORBIDDEN {
+                // Token might have been revoked, next fetch should authenticate again
+                if let Some(authenticator) = &self.authenticator {
+                    authenticator.invalidate().await;
+                }
+            }
+            if status_code == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                if let Some(retry_after) = retry_after {
+                    return Err(Error::ConfigStoreRateLimited(retry_after));
+                }
+            }
+            if status_code.is_server_error() {
+                return Err(Error::VaultConfigStoreServerError(std::io::Error::other(
+                    message,
+                )));
+            }
+            return Err(Error::VaultConfigStoreClientError(std::io::Error::other(
+                message,
+            )));
+        }
+
+        let body: Value = serde_json::from_str(&res_txt).map_err(Error::JsonSerializationError)?;
+        let data = self.extract_secret_data(body)?;
+
+        serde_json::to_string(&data).map_err(Error::JsonSerializationError)
+    }
+}
+
+/// Joins `errors` array Vault returns on failed requests into single message.
+fn vault_error_message(body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|v| v.get("errors").and_then(|e| e.as_array()).cloned())
+        .map(|errors| {
+            errors
+                .iter()
+                .filter_map(|e| e.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        })
+        .filter(|message| !message.is_empty())
+        .unwrap_or_else(|| body.to_string())
 }
+
 #[async_trait]
 impl IConfigStore for VaultConfigStore {
     async fn get_config(
@@ -25,9 +225,37 @@ impl IConfigStore for VaultConfigStore {
         query_params: Option<HashMap<String, String>>,
         headers: Option<HashMap<String, String>>,
     ) -> Result<String, Error> {
-        // HTTP client logic (could use reqwest, etc.)
+        let mut merged_headers_map: HashMap<String, String> = HashMap::new();
+        merged_headers_map.extend(self.config.headers.clone());
+        merged_headers_map.extend(headers.unwrap_or_default());
+
+        let url = self.secret_url(&query_params.unwrap_or_default())?;
+
+        with_retries(&self.config.retry, || self.fetch(&url, &merged_headers_map)).await
+    }
+
+    /// Probes `sys/health`, standby nodes are considered healthy as they forward requests.
+    async fn health_check(&self) -> Result<(), Error> {
+        let url = format!(
+            "{}/v1/sys/health?standbyok=true&perfstandbyok=true",
+            self.config.url.trim_end_matches('/')
+        );
+        let response = self
+            .client()?
+            .get(url)
+            .send()
+            .await
+            .map_err(Error::VaultConfigStoreError)?;
 
-        println!("Fetching config from HTTP at {}", self.config.url);
-        Ok(String::from("Vault config"))
+        let message = match response.status().as_u16() {
+            200..=299 => return Ok(()),
+            501 => String::from("Vault is not initialized"),
+            503 => String::from("Vault is sealed"),
+            472 => String::from("Vault is disaster recovery secondary"),
+            status => format!("Vault health check responded with {}", status),
+        };
+        Err(Error::VaultConfigStoreServerError(std::io::Error::other(
+            message,
+        )))
     }
 }
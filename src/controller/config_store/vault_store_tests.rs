@@ -0,0 +1,412 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::iconfigstore::IConfigStore;
+    use crate::contract::lib::Error;
+    use crate::controller::config_store::retry::RetryOptions;
+    use crate::controller::config_store::vault_auth::{VaultAuthMethod, VaultTokenSlot};
+    use crate::controller::config_store::vault_store::{
+        VaultConfigStore, VaultConfigStoreConnectionDetails, VaultKvVersion,
+    };
+    use crate::controller::utils::file_format::ConfigFormat;
+    use crate::controller::utils::parsers::text_to_json::try_parse_file_to_json;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::env::temp_dir;
+    use std::time::Duration;
+    use wiremock::matchers::{body_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn vault_store(server: &MockServer, kv_version: VaultKvVersion) -> VaultConfigStore {
+        VaultConfigStore::new(
+            VaultConfigStoreConnectionDetails {
+                url: server.uri(),
+                mount: String::from("secret"),
+                kv_version,
+                headers: HashMap::new(),
+                auth: None,
+                connect_timeout: Duration::from_secs(5),
+                request_timeout: Duration::from_secs(5),
+                retry: RetryOptions {
+                    max_retries: 2,
+                    initial_backoff: Duration::from_millis(1),
+                    max_backoff: Duration::from_millis(50),
+                },
+            },
+            VaultTokenSlot::default(),
+        )
+    }
+
+    fn authenticated_vault_store(
+        server: &MockServer,
+        auth: VaultAuthMethod,
+        slot: VaultTokenSlot,
+    ) -> VaultConfigStore {
+        VaultConfigStore::new(
+            VaultConfigStoreConnectionDetails {
+                url: server.uri(),
+                mount: String::from("secret"),
+                kv_version: VaultKvVersion::V2,
+                headers: HashMap::new(),
+                auth: Some(auth),
+                connect_timeout: Duration::from_secs(5),
+                request_timeout: Duration::from_secs(5),
+                retry: RetryOptions {
+                    max_retries: 2,
+                    initial_backoff: Duration::from_millis(1),
+                    max_backoff: Duration::from_millis(50),
+                },
+            },
+            slot,
+        )
+    }
+
+    fn login_response(token: &str, lease_duration: u64) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "auth": {
+                "client_token": token,
+                "accessor": "accessor",
+                "policies": ["default"],
+                "lease_duration": lease_duration,
+                "renewable": true
+            }
+        }))
+    }
+
+    async fn mount_secret_for_token(server: &MockServer, token: &str) {
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/app"))
+            .and(header("X-Vault-Token", token))
+            .respond_with(kv2_response(json!({"key": "value"}), 1))
+            .mount(server)
+            .await;
+    }
+
+    fn params(values: &[(&str, &str)]) -> Option<HashMap<String, String>> {
+        Some(
+            values
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    fn kv2_response(data: serde_json::Value, version: u64) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "request_id": "7a1b2c3d",
+            "lease_id": "",
+            "renewable": false,
+            "lease_duration": 0,
+            "data": {
+                "data": data,
+                "metadata": {
+                    "created_time": "2024-10-01T10:00:00.000000Z",
+                    "deletion_time": "",
+                    "destroyed": false,
+                    "version": version
+                }
+            }
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_kv2_secret_is_returned_as_json_document() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/app/config"))
+            .respond_with(kv2_response(
+                json!({"dbConfig": {"host": "db", "port": 5432}}),
+                1,
+            ))
+            .mount(&server)
+            .await;
+
+        let config = vault_store(&server, VaultKvVersion::V2)
+            .get_config(params(&[("path", "app/config")]), None)
+            .await
+            .expect("Secret should be returned");
+
+        match try_parse_file_to_json(&config).expect("Secret should be parseable") {
+            ConfigFormat::Json(value) => {
+                assert_eq!(value, json!({"dbConfig": {"host": "db", "port": 5432}}))
+            }
+            _ => panic!("Expected JSON document"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kv2_version_pin_and_mount_are_taken_from_params() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/team-kv/data/app/config"))
+            .and(query_param("version", "3"))
+            .respond_with(kv2_response(json!({"release": "v3"}), 3))
+            .mount(&server)
+            .await;
+
+        let config = vault_store(&server, VaultKvVersion::V2)
+            .get_config(
+                params(&[
+                    ("mount", "team-kv"),
+                    ("path", "/app/config"),
+                    ("version", "3"),
+                ]),
+                None,
+            )
+            .await
+            .expect("Pinned version should be returned");
+
+        assert_eq!(config, "{\"release\":\"v3\"}");
+    }
+
+    #[tokio::test]
+    async fn test_kv1_secret_is_read_without_data_prefix() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/app/config"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "lease_duration": 2764800,
+                "renewable": false,
+                "data": {"apiKey": "abc"}
+            })))
+            .mount(&server)
+            .await;
+
+        let config = vault_store(&server, VaultKvVersion::V1)
+            .get_config(params(&[("path", "app/config")]), None)
+            .await
+            .expect("Secret should be returned");
+
+        assert_eq!(config, "{\"apiKey\":\"abc\"}");
+    }
+
+    #[tokio::test]
+    async fn test_missing_path_param_is_rejected() {
+        let server = MockServer::start().await;
+
+        let result = vault_store(&server, VaultKvVersion::V2)
+            .get_config(params(&[("mount", "secret")]), None)
+            .await;
+
+        match result {
+            Err(Error::MissingConfigStoreParameter(param)) => assert_eq!(param, "path"),
+            _ => panic!("Expected Error::MissingConfigStoreParameter"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vault_errors_are_mapped_to_client_and_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/missing"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({"errors": []})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/sealed"))
+            .respond_with(
+                ResponseTemplate::new(503).set_body_json(json!({"errors": ["Vault is sealed"]})),
+            )
+            .mount(&server)
+            .await;
+
+        let store = vault_store(&server, VaultKvVersion::V2);
+
+        match store.get_config(params(&[("path", "missing")]), None).await {
+            Err(Error::VaultConfigStoreClientError(_)) => {}
+            _ => panic!("Expected Error::VaultConfigStoreClientError"),
+        }
+        match store.get_config(params(&[("path", "sealed")]), None).await {
+            Err(Error::VaultConfigStoreServerError(err)) => {
+                assert_eq!(format!("{}", err), "Vault is sealed")
+            }
+            _ => panic!("Expected Error::VaultConfigStoreServerError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kubernetes_login_token_is_cached_between_store_instances() {
+        let server = MockServer::start().await;
+        let jwt_path = temp_dir().join(format!("vault-test-jwt-{}", std::process::id()));
+        std::fs::write(&jwt_path, "service-account-jwt\n").unwrap();
+
+        Mock::given(method("POST"))
+            .and(path("/v1/auth/kubernetes/login"))
+            .and(body_json(
+                json!({"role": "operator", "jwt": "service-account-jwt"}),
+            ))
+            .respond_with(login_response("k8s-token", 3600))
+            .expect(1)
+            .mount(&server)
+            .await;
+        mount_secret_for_token(&server, "k8s-token").await;
+
+        let auth = VaultAuthMethod::Kubernetes {
+            mount_path: String::from("kubernetes"),
+            role: String::from("operator"),
+            jwt_path: jwt_path.to_string_lossy().to_string(),
+        };
+        let slot = VaultTokenSlot::default();
+
+        // Store instances sharing token slot log in only once
+        for _ in 0..3 {
+            let config = authenticated_vault_store(&server, auth.clone(), slot.clone())
+                .get_config(params(&[("path", "app")]), None)
+                .await
+                .expect("Secret should be returned");
+            assert_eq!(config, "{\"key\":\"value\"}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_app_role_token_is_renewed_before_ttl_runs_out() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/auth/approle/login"))
+            .and(body_json(json!({"role_id": "role", "secret_id": "secret"})))
+            .respond_with(login_response("approle-token", 3))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/auth/token/renew-self"))
+            .and(header("X-Vault-Token", "approle-token"))
+            .respond_with(login_response("approle-token", 3))
+            .expect(1)
+            .mount(&server)
+            .await;
+        mount_secret_for_token(&server, "approle-token").await;
+
+        let store = authenticated_vault_store(
+            &server,
+            VaultAuthMethod::AppRole {
+                mount_path: String::from("approle"),
+                role_id: String::from("role"),
+                secret_id: String::from("secret"),
+            },
+            VaultTokenSlot::default(),
+        );
+
+        store
+            .get_config(params(&[("path", "app")]), None)
+            .await
+            .expect("Secret should be returned after login");
+        tokio::time::sleep(Duration::from_millis(2100)).await;
+        store
+            .get_config(params(&[("path", "app")]), None)
+            .await
+            .expect("Secret should be returned after renewal");
+    }
+
+    #[tokio::test]
+    async fn test_changed_credentials_trigger_new_login() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/auth/approle/login"))
+            .and(body_json(json!({"role_id": "role", "secret_id": "old"})))
+            .respond_with(login_response("old-token", 3600))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/auth/approle/login"))
+            .and(body_json(
+                json!({"role_id": "role", "secret_id": "rotated"}),
+            ))
+            .respond_with(login_response("new-token", 3600))
+            .expect(1)
+            .mount(&server)
+            .await;
+        mount_secret_for_token(&server, "old-token").await;
+        mount_secret_for_token(&server, "new-token").await;
+
+        let slot = VaultTokenSlot::default();
+        for secret_id in ["old", "rotated"] {
+            authenticated_vault_store(
+                &server,
+                VaultAuthMethod::AppRole {
+                    mount_path: String::from("approle"),
+                    role_id: String::from("role"),
+                    secret_id: String::from(secret_id),
+                },
+                slot.clone(),
+            )
+            .get_config(params(&[("path", "app")]), None)
+            .await
+            .expect("Secret should be returned");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_static_token_is_sent_without_login() {
+        let server = MockServer::start().await;
+        mount_secret_for_token(&server, "static-token").await;
+
+        let config = authenticated_vault_store(
+            &server,
+            VaultAuthMethod::Token(String::from("static-token")),
+            VaultTokenSlot::default(),
+        )
+        .get_config(params(&[("path", "app")]), None)
+        .await
+        .expect("Secret should be returned");
+
+        assert_eq!(config, "{\"key\":\"value\"}");
+    }
+
+    #[tokio::test]
+    async fn test_standby_errors_are_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/app"))
+            .respond_with(ResponseTemplate::new(503).set_body_json(json!({"errors": []})))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/app"))
+            .respond_with(kv2_response(json!({"key": "value"}), 1))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = vault_store(&server, VaultKvVersion::V2)
+            .get_config(params(&[("path", "app")]), None)
+            .await
+            .expect("Secret should be returned after retry");
+
+        assert_eq!(config, "{\"key\":\"value\"}");
+    }
+
+    #[tokio::test]
+    async fn test_health_check_reports_sealed_vault() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/sys/health"))
+            .and(query_param("standbyok", "true"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/sys/health"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"sealed": false})))
+            .mount(&server)
+            .await;
+
+        let store = vault_store(&server, VaultKvVersion::V2);
+
+        match store.health_check().await {
+            Err(Error::VaultConfigStoreServerError(err)) => {
+                assert_eq!(format!("{}", err), "Vault is sealed")
+            }
+            _ => panic!("Expected Error::VaultConfigStoreServerError"),
+        }
+        store
+            .health_check()
+            .await
+            .expect("Unsealed Vault should be healthy");
+    }
+}
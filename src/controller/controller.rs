@@ -1,19 +1,27 @@
-use crate::contract::ireconcilable::{ControllerReconcilableTargetTypeBounds, IReconcilable};
+use crate::contract::ireconcilable::{
+    ControllerReconcilableTargetTypeBounds, IReconcilable, ReconcilableTargetTypeBounds,
+};
 use crate::contract::lib::Error;
 use crate::contract::lib::Result;
 use crate::controller::utils::context::Context;
+use crate::controller::v1alpha1::claim_triggers::{
+    store_change_trigger, store_trigger, target_trigger, StoreClaims,
+};
 use crate::controller::v1alpha1::controller::crds;
-use crate::controller::v1alpha1::crd::claim::{ConfigMapClaim, SecretClaim};
+use crate::controller::v1alpha1::crd::claim::{
+    ConfigMapClaim, HasClaimData, HasTarget, SecretClaim,
+};
 use crate::controller::v1alpha1::crd::configuration_store::{
     ClusterConfigurationStore, ConfigurationStore,
 };
 use crate::observability::telemetry;
 use async_trait::async_trait;
-use futures::stream::StreamExt;
+use futures::stream::{StreamExt, TryStreamExt};
 use k8s_openapi::api::core::v1::ConfigMap;
 use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
 use k8s_openapi::NamespaceResourceScope;
 use kube::api::ApiResource;
+use kube::core::PartialObjectMeta;
 use kube::{
     api::{
         Api, DynamicObject, GroupVersionKind, ListParams, Patch, PatchParams, PostParams,
@@ -24,7 +32,9 @@ use kube::{
         controller::{Action, Controller},
         events::{Event, EventType, Recorder, Reporter},
         finalizer::{finalizer, Event as Finalizer},
-        watcher::Config,
+        metadata_watcher, reflector,
+        watcher::{watcher, Config},
+        WatchStreamExt,
     },
     CustomResource, Resource,
 };
@@ -75,9 +85,14 @@ where
     Action::requeue(Duration::from_secs(60))
 }
 
-pub async fn run<T: Resource + IReconcilable>(data: Context)
+/// Runs claim controller. Besides claims, targets of type `Target` and both store kinds are
+/// watched, so their changes sync affected claims without waiting for refresh interval.
+/// Store changes announced by webhook do the same. Only metadata of targets is watched, so
+/// content of every Secret in the cluster is not streamed to the operator.
+pub async fn run<T: Resource + IReconcilable, Target>(data: Context)
 where
-    T: ControllerReconcilableTargetTypeBounds,
+    T: ControllerReconcilableTargetTypeBounds + HasClaimData + HasTarget,
+    Target: ReconcilableTargetTypeBounds + 'static,
 {
     let api: Api<T> = Api::all((*data.client).clone());
 
@@ -87,7 +102,38 @@ where
         std::process::exit(1);
     }
 
-    Controller::new(api, Config::default())
+    let (claims, writer) = reflector::store();
+    let index = Arc::new(StoreClaims::<T>::default());
+    let claim_events = {
+        let index = index.clone();
+        reflector(writer, watcher(api, Config::default()))
+            .inspect_ok(move |event| index.apply_watcher_event(event))
+            .applied_objects()
+    };
+    let client = (*data.client).clone();
+    let resync = data.claim_resync.clone();
+
+    Controller::for_stream(claim_events, claims.clone())
+        .watches_stream(
+            metadata_watcher(Api::<Target>::all(client.clone()), Config::default())
+                .touched_objects(),
+            target_trigger::<T, PartialObjectMeta<Target>>(claims, resync.clone()),
+        )
+        .reconcile_on(store_trigger::<T, ConfigurationStore>(
+            watcher(Api::all(client.clone()), Config::default()).default_backoff(),
+            index.clone(),
+            resync.clone(),
+        ))
+        .reconcile_on(store_trigger::<T, ClusterConfigurationStore>(
+            watcher(Api::all(client), Config::default()).default_backoff(),
+            index.clone(),
+            resync.clone(),
+        ))
+        .reconcile_on(store_change_trigger(
+            data.store_changes.subscribe(),
+            index,
+            resync,
+        ))
         .shutdown_on_signal()
         .run(reconcile::<T>, error_policy::<T>, Arc::new(data))
         .for_each(|res| async move {
@@ -1,14 +1,17 @@
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::controller::config_store::content_cache::ParsedContentCache;
+use crate::controller::config_store::store_registry::ConfigStoreRegistry;
+use crate::controller::utils::config::Config;
 use crate::controller::utils::context::Context;
+use crate::controller::v1alpha1::claim_triggers::{ResyncRequests, StoreChange, StoreChanges};
 use crate::controller::v1alpha1::crd_client::CrdClient;
 use crate::observability::metrics::Metrics;
 use chrono::{DateTime, Utc};
 use kube::runtime::events::{Recorder, Reporter};
 use kube::Client;
 use serde::Serialize;
-use crate::controller::utils::config::Config;
 
 /// Diagnostics to be exposed by the web server
 #[derive(Clone, Serialize)]
@@ -39,6 +42,14 @@ pub struct State {
     diagnostics: Arc<RwLock<Diagnostics>>,
     /// Metrics
     metrics: Arc<Metrics>,
+    /// Config store instances, kept between reconciles
+    config_stores: Arc<ConfigStoreRegistry>,
+    /// Documents parsed from store content, kept while content is unchanged
+    parsed_content: Arc<ParsedContentCache>,
+    /// Claims to sync on next reconcile, requested by target and store watches
+    claim_resync: Arc<ResyncRequests>,
+    /// Store changes announced by webhook
+    store_changes: Arc<StoreChanges>,
 }
 
 /// State wrapper around the controller outputs for the web server
@@ -56,6 +67,11 @@ impl State {
         self.diagnostics.read().await.clone()
     }
 
+    /// Requests resync of claims using the store, returns `false` when no controller runs
+    pub fn notify_store_change(&self, change: StoreChange) -> bool {
+        self.store_changes.publish(change)
+    }
+
     // Create a Controller Context that can update State
     pub fn to_context(&self, client: Arc<Client>) -> Arc<Context> {
         Arc::new(Context {
@@ -63,6 +79,10 @@ impl State {
             metrics: self.metrics.clone(),
             v1alpha1: Arc::new(CrdClient::new(client.clone())),
             api_client: Arc::new(CrdClient::new(client.clone())),
+            config_stores: self.config_stores.clone(),
+            parsed_content: self.parsed_content.clone(),
+            claim_resync: self.claim_resync.clone(),
+            store_changes: self.store_changes.clone(),
             // diagnostics: self.diagnostics.clone(),
         })
     }
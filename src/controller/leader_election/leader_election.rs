@@ -1,39 +1,108 @@
 use crate::contract::clients::{K8sClient, CONTROLLER_LEASE_NAME};
 use crate::contract::lib::Error;
+use crate::controller::utils::config::Config;
 use crate::controller::utils::context::Context;
-use crate::controller::utils::config;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use k8s_openapi::api::coordination::v1::{Lease, LeaseSpec};
-use k8s_openapi::apimachinery::pkg::apis::meta::v1::{MicroTime, OwnerReference};
-use kube::api::{Patch, PatchParams, PostParams};
-use kube::{Api, Client};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{MicroTime, ObjectMeta};
+use kube::api::PostParams;
+use kube::core::ErrorResponse;
 use log::{debug, error, info, warn};
-use serde_json::json;
-use std::env;
-use std::sync::Arc;
-use std::thread::sleep;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
-use crate::controller::utils::config::Config;
+
+/// Lease timing. Renew deadline has to be shorter than lease duration, so leader stops its
+/// controllers before standby replica may consider the lease expired.
+#[derive(Debug, Clone)]
+pub struct LeaseConfig {
+    pub lease_duration: Duration,
+    pub renew_deadline: Duration,
+    pub retry_period: Duration,
+}
+
+impl LeaseConfig {
+    pub fn from_env() -> Self {
+        LeaseConfig {
+            lease_duration: Config::leader_election_lease_duration(),
+            renew_deadline: Config::leader_election_renew_deadline(),
+            retry_period: Config::leader_election_retry_period(),
+        }
+    }
+
+    /// Leader has to retry renewal at least once within renew deadline, and stop before
+    /// lease may expire for standby replicas.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.renew_deadline <= self.retry_period {
+            return Err(Error::InvalidLeaderElectionConfig(format!(
+                "renew deadline {:?} must be longer than retry period {:?}",
+                self.renew_deadline, self.retry_period
+            )));
+        }
+        if self.renew_deadline >= self.lease_duration {
+            return Err(Error::InvalidLeaderElectionConfig(format!(
+                "renew deadline {:?} must be shorter than lease duration {:?}",
+                self.renew_deadline, self.lease_duration
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Lease record as last seen by this replica, with local time it was seen to change at.
+/// Expiry is measured from that time on local monotonic clock, as client-go does, so clock
+/// skew between replicas does not matter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObservedLease {
+    resource_version: Option<String>,
+    renew_time: Option<MicroTime>,
+    pub observed_at: Instant,
+}
+
+impl ObservedLease {
+    /// Keeps time of previous observation while the lease record stays the same.
+    pub fn observe(previous: Option<&ObservedLease>, lease: &Lease, now: Instant) -> Self {
+        let resource_version = lease.metadata.resource_version.clone();
+        let renew_time = lease.spec.as_ref().and_then(|spec| spec.renew_time.clone());
+        let observed_at = match previous {
+            Some(previous)
+                if previous.resource_version == resource_version
+                    && previous.renew_time == renew_time =>
+            {
+                previous.observed_at
+            }
+            _ => now,
+        };
+        ObservedLease {
+            resource_version,
+            renew_time,
+            observed_at,
+        }
+    }
+}
 
 pub struct LeaderElection {
     cancel_token: Arc<CancellationToken>,
     ctx: Arc<Context>,
+    config: LeaseConfig,
+    observed: Mutex<Option<ObservedLease>>,
 }
 
 impl LeaderElection {
     pub fn new(cancel_token: Arc<CancellationToken>, ctx: Arc<Context>) -> Self {
-        LeaderElection { cancel_token, ctx }
+        LeaderElection {
+            cancel_token,
+            ctx,
+            config: LeaseConfig::from_env(),
+            observed: Mutex::new(None),
+        }
     }
-    pub fn enabled(&self) -> bool {
-        let has_namespace = env::var("KUBERNETES_NAMESPACE")
-            .map(|x| true)
-            .unwrap_or_else(|_| false);
-        let has_pod = env::var("KUBERNETES_POD_NAME")
-            .map(|x| true)
-            .unwrap_or_else(|_| false);
 
-        has_pod && has_namespace
+    pub fn config(&self) -> &LeaseConfig {
+        &self.config
+    }
+    pub fn enabled(&self) -> bool {
+        Config::kubernetes_pod_name().is_some() && Config::kubernetes_namespace().is_some()
     }
 
     async fn run_cancellable<F, Fut, T>(&self, f: F) -> Result<T, Error>
@@ -50,70 +119,252 @@ impl LeaderElection {
         }
     }
 
-    pub async fn claim_leadership_loop(&self) -> Result<Option<Lease>, Error> {
+    fn identity(&self) -> String {
+        Config::kubernetes_pod_name().unwrap_or_default()
+    }
+
+    fn namespace(&self) -> String {
+        Config::kubernetes_namespace().unwrap_or("default".to_string())
+    }
+
+    /// Waits as standby replica until lease is acquired.
+    pub async fn claim_leadership_loop(&self) -> Result<(), Error> {
         info!("Trying to acquire lease...");
 
         self.run_cancellable(|| async move {
             loop {
-                let result = self.ctx.api_client.try_create_lease_for_current_pod().await;
-                match result {
-                    Ok(lease) => return Ok(Some(lease)),
-                    Err(e) => {
-                        sleep(Duration::from_secs(10));
-                        debug!("Error when acquiring lease... {:?}", e);
-                        continue;
+                match self.try_acquire_or_renew().await {
+                    Ok(true) => {
+                        info!("Acquired leadership with lease: {}", CONTROLLER_LEASE_NAME);
+                        return Ok(());
                     }
+                    Ok(false) => debug!("Lease is held by another replica"),
+                    Err(e) => warn!("Error when acquiring lease: {:?}", e),
                 }
+                tokio::time::sleep(self.config.retry_period).await;
             }
         })
         .await
     }
-    pub async fn refresh_leadership_loop(&self) {
-        let mut refresh_interval = tokio::time::interval(Duration::from_secs(10));
-        let namespace = Config::kubernetes_namespace().unwrap_or("default".to_string());
-
-        let _ = self
-            .run_cancellable(|| async move {
-                loop {
-                    refresh_interval.tick().await;
-                    let mut lease = match self
-                        .ctx
-                        .api_client
-                        .get_lease(&CONTROLLER_LEASE_NAME, &namespace.as_str())
-                        .await
-                    {
-                        Ok(lease) => lease,
-                        Err(e) => {
-                            error!("Failed to get lease on refresh attempt: {:?}", e);
-                            break;
-                        }
-                    };
 
-                    let mut lease_spec = lease.spec.as_mut().unwrap();
-                    lease_spec.renew_time = Some(MicroTime(Utc::now()));
+    /// Renews lease every retry period. Returns once leadership is lost, i.e. lease was taken
+    /// over or could not be renewed within renew deadline.
+    pub async fn refresh_leadership_loop(&self) {
+        let mut last_renewal = Instant::now();
+        loop {
+            tokio::time::sleep(self.config.retry_period).await;
+            let attempt =
+                tokio::time::timeout(self.config.renew_deadline, self.try_acquire_or_renew()).await;
+            match attempt {
+                Ok(Ok(true)) => {
+                    debug!("Successfully renewed lease: {}", CONTROLLER_LEASE_NAME);
+                    last_renewal = Instant::now();
+                }
+                Ok(Ok(false)) => {
+                    error!(
+                        "Lease {} was taken over by another replica",
+                        CONTROLLER_LEASE_NAME
+                    );
+                    return;
+                }
+                Ok(Err(e)) => warn!("Failed to renew lease: {:?}", e),
+                Err(_) => warn!("Renewing lease timed out"),
+            }
+            if last_renewal.elapsed() >= self.config.renew_deadline {
+                error!(
+                    "Failed to renew lease {} within {:?}",
+                    CONTROLLER_LEASE_NAME, self.config.renew_deadline
+                );
+                return;
+            }
+        }
+    }
 
-                    match self
-                        .ctx
-                        .api_client
+    /// Gives up held lease on shutdown, so standby replica does not wait for it to expire.
+    pub async fn release(&self) {
+        let namespace = self.namespace();
+        let client = &self.ctx.api_client;
+        let result = match client.get_lease(CONTROLLER_LEASE_NAME, &namespace).await {
+            Ok(lease) => match released(lease.spec.as_ref(), &self.identity(), Utc::now()) {
+                Some(spec) => {
+                    let lease = Lease {
+                        spec: Some(spec),
+                        ..lease
+                    };
+                    client
                         .replace_lease(
-                            &CONTROLLER_LEASE_NAME,
+                            CONTROLLER_LEASE_NAME,
                             &PostParams::default(),
                             &lease,
-                            namespace.as_str(),
+                            &namespace,
                         )
                         .await
-                    {
-                        Ok(_) => {
-                            info!("Successfully renewed lease: {}", CONTROLLER_LEASE_NAME)
-                        }
-                        Err(e) => {
-                            error!("Failed to renew lease: {:?}", e);
-                            break;
-                        }
-                    }
+                        .map(|_| info!("Released lease: {}", CONTROLLER_LEASE_NAME))
                 }
-                Ok(())
-            })
+                None => Ok(()),
+            },
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!("Failed to release lease: {:?}", e);
+        }
+    }
+
+    /// Acquires or renews lease once, returning whether this replica holds it. Lease is
+    /// replaced with resourceVersion it was read with, so concurrent update by another
+    /// replica makes the attempt fail instead of overwriting it.
+    async fn try_acquire_or_renew(&self) -> Result<bool, Error> {
+        let namespace = self.namespace();
+        let identity = self.identity();
+        let client = &self.ctx.api_client;
+        let now = Utc::now();
+
+        let lease = match client.get_lease(CONTROLLER_LEASE_NAME, &namespace).await {
+            Ok(lease) => lease,
+            Err(Error::KubeError(kube::Error::Api(ErrorResponse { code: 404, .. }))) => {
+                let lease = Lease {
+                    metadata: ObjectMeta {
+                        name: Some(CONTROLLER_LEASE_NAME.to_string()),
+                        namespace: Some(namespace.clone()),
+                        ..Default::default()
+                    },
+                    spec: acquire_or_renew(None, &identity, now, false, self.config.lease_duration),
+                };
+                let result = client
+                    .create_lease(&PostParams::default(), &lease, &namespace)
+                    .await;
+                return self.acquired(result);
+            }
+            Err(e) => return Err(e),
+        };
+
+        let observed_at = self.observe(&lease);
+        let expired = lease.spec.as_ref().is_none_or(|spec| {
+            lease_expired(
+                spec,
+                observed_at,
+                Instant::now(),
+                self.config.lease_duration,
+            )
+        });
+        let Some(spec) = acquire_or_renew(
+            lease.spec.as_ref(),
+            &identity,
+            now,
+            expired,
+            self.config.lease_duration,
+        ) else {
+            return Ok(false);
+        };
+        let lease = Lease {
+            spec: Some(spec),
+            ..lease
+        };
+        let result = client
+            .replace_lease(
+                CONTROLLER_LEASE_NAME,
+                &PostParams::default(),
+                &lease,
+                &namespace,
+            )
             .await;
+        self.acquired(result)
     }
+
+    /// Records lease as seen now, returning local time it was seen to change at.
+    fn observe(&self, lease: &Lease) -> Instant {
+        let mut observed = self.observed.lock().unwrap();
+        let current = ObservedLease::observe(observed.as_ref(), lease, Instant::now());
+        let observed_at = current.observed_at;
+        *observed = Some(current);
+        observed_at
+    }
+
+    /// Conflict means another replica created or updated the lease first.
+    fn acquired(&self, result: Result<Lease, Error>) -> Result<bool, Error> {
+        match result {
+            Ok(lease) => {
+                self.observe(&lease);
+                Ok(true)
+            }
+            Err(Error::KubeError(kube::Error::Api(ErrorResponse { code: 409, .. }))) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Whether lease was not renewed within its duration since it was observed to change.
+/// Duration written by the holder is honored, `lease_duration` only applies when it is missing.
+pub fn lease_expired(
+    spec: &LeaseSpec,
+    observed_at: Instant,
+    now: Instant,
+    lease_duration: Duration,
+) -> bool {
+    let duration = spec
+        .lease_duration_seconds
+        .and_then(|seconds| u64::try_from(seconds).ok())
+        .map(Duration::from_secs)
+        .unwrap_or(lease_duration);
+    now.saturating_duration_since(observed_at) >= duration
+}
+
+/// Lease spec to write for `identity`: renewed when it already holds the lease, taken over
+/// when lease is free or `expired`. `None` while another holder's lease is valid.
+pub fn acquire_or_renew(
+    current: Option<&LeaseSpec>,
+    identity: &str,
+    now: DateTime<Utc>,
+    expired: bool,
+    lease_duration: Duration,
+) -> Option<LeaseSpec> {
+    let lease_duration_seconds = Some(lease_duration.as_secs().try_into().unwrap_or(i32::MAX));
+    let Some(current) = current else {
+        return Some(LeaseSpec {
+            holder_identity: Some(identity.to_string()),
+            acquire_time: Some(MicroTime(now)),
+            renew_time: Some(MicroTime(now)),
+            lease_duration_seconds,
+            lease_transitions: Some(0),
+            ..Default::default()
+        });
+    };
+
+    let holder = current
+        .holder_identity
+        .as_deref()
+        .filter(|holder| !holder.is_empty());
+    if holder == Some(identity) {
+        return Some(LeaseSpec {
+            renew_time: Some(MicroTime(now)),
+            lease_duration_seconds,
+            ..current.clone()
+        });
+    }
+    if holder.is_some() && !expired {
+        return None;
+    }
+    Some(LeaseSpec {
+        holder_identity: Some(identity.to_string()),
+        acquire_time: Some(MicroTime(now)),
+        renew_time: Some(MicroTime(now)),
+        lease_duration_seconds,
+        lease_transitions: Some(current.lease_transitions.unwrap_or(0) + 1),
+        ..current.clone()
+    })
+}
+
+/// Lease spec freeing lease held by `identity`, `None` when held by someone else.
+pub fn released(
+    current: Option<&LeaseSpec>,
+    identity: &str,
+    now: DateTime<Utc>,
+) -> Option<LeaseSpec> {
+    let current = current.filter(|spec| spec.holder_identity.as_deref() == Some(identity))?;
+    Some(LeaseSpec {
+        holder_identity: None,
+        renew_time: Some(MicroTime(now)),
+        lease_duration_seconds: Some(1),
+        ..current.clone()
+    })
 }
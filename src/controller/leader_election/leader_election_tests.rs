@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use crate::controller::leader_election::leader_election::{
+        acquire_or_renew, lease_expired, released, LeaseConfig, ObservedLease,
+    };
+    use chrono::Utc;
+    use k8s_openapi::api::coordination::v1::{Lease, LeaseSpec};
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::{MicroTime, ObjectMeta};
+    use std::time::{Duration, Instant};
+
+    const LEASE_DURATION: Duration = Duration::from_secs(15);
+
+    fn held_by(holder: &str, transitions: i32) -> LeaseSpec {
+        let renewed = Utc::now();
+        LeaseSpec {
+            holder_identity: Some(holder.to_string()),
+            acquire_time: Some(MicroTime(renewed)),
+            renew_time: Some(MicroTime(renewed)),
+            lease_duration_seconds: Some(15),
+            lease_transitions: Some(transitions),
+            ..Default::default()
+        }
+    }
+
+    fn lease(resource_version: &str, spec: LeaseSpec) -> Lease {
+        Lease {
+            metadata: ObjectMeta {
+                resource_version: Some(resource_version.to_string()),
+                ..Default::default()
+            },
+            spec: Some(spec),
+        }
+    }
+
+    #[test]
+    fn test_lease_expires_after_duration_written_by_holder() {
+        let observed_at = Instant::now();
+        let now = observed_at + Duration::from_secs(10);
+        let mut spec = held_by("a", 0);
+
+        assert!(!lease_expired(&spec, observed_at, now, LEASE_DURATION));
+        spec.lease_duration_seconds = Some(5);
+        assert!(lease_expired(&spec, observed_at, now, LEASE_DURATION));
+        spec.lease_duration_seconds = None;
+        assert!(!lease_expired(&spec, observed_at, now, LEASE_DURATION));
+        assert!(lease_expired(
+            &spec,
+            observed_at,
+            observed_at + LEASE_DURATION,
+            LEASE_DURATION
+        ));
+    }
+
+    #[test]
+    fn test_expiry_is_measured_from_last_observed_change() {
+        let first_seen = Instant::now();
+        let spec = held_by("a", 0);
+        let observed = ObservedLease::observe(None, &lease("1", spec.clone()), first_seen);
+        assert_eq!(observed.observed_at, first_seen);
+
+        // Unchanged lease keeps time it was first seen at, however old its renewTime is
+        let later = first_seen + Duration::from_secs(20);
+        let unchanged = ObservedLease::observe(Some(&observed), &lease("1", spec.clone()), later);
+        assert_eq!(unchanged.observed_at, first_seen);
+        assert!(lease_expired(
+            &spec,
+            unchanged.observed_at,
+            later,
+            LEASE_DURATION
+        ));
+
+        // Renewal by the holder restarts expiry, regardless of clock of the holder
+        let renewed_spec = LeaseSpec {
+            renew_time: Some(MicroTime(Utc::now() - chrono::TimeDelta::hours(1))),
+            ..spec
+        };
+        let renewed =
+            ObservedLease::observe(Some(&unchanged), &lease("2", renewed_spec.clone()), later);
+        assert_eq!(renewed.observed_at, later);
+        assert!(!lease_expired(
+            &renewed_spec,
+            renewed.observed_at,
+            later + Duration::from_secs(5),
+            LEASE_DURATION
+        ));
+    }
+
+    #[test]
+    fn test_valid_lease_of_another_holder_is_not_taken() {
+        let now = Utc::now();
+
+        assert!(
+            acquire_or_renew(Some(&held_by("a", 0)), "b", now, false, LEASE_DURATION).is_none()
+        );
+    }
+
+    #[test]
+    fn test_holder_renews_lease_keeping_transitions() {
+        let now = Utc::now();
+        let current = held_by("a", 3);
+        let renewed = acquire_or_renew(Some(&current), "a", now, false, LEASE_DURATION).unwrap();
+
+        assert_eq!(renewed.renew_time, Some(MicroTime(now)));
+        assert_eq!(renewed.acquire_time, current.acquire_time);
+        assert_eq!(renewed.lease_transitions, Some(3));
+    }
+
+    #[test]
+    fn test_expired_or_released_lease_is_taken_over() {
+        let now = Utc::now();
+
+        let taken =
+            acquire_or_renew(Some(&held_by("a", 3)), "b", now, true, LEASE_DURATION).unwrap();
+        assert_eq!(taken.holder_identity.as_deref(), Some("b"));
+        assert_eq!(taken.acquire_time, Some(MicroTime(now)));
+        assert_eq!(taken.lease_transitions, Some(4));
+
+        let free = released(Some(&held_by("a", 3)), "a", now).unwrap();
+        let taken = acquire_or_renew(Some(&free), "b", now, false, LEASE_DURATION).unwrap();
+        assert_eq!(taken.holder_identity.as_deref(), Some("b"));
+        assert_eq!(taken.lease_transitions, Some(4));
+
+        let created = acquire_or_renew(None, "b", now, false, LEASE_DURATION).unwrap();
+        assert_eq!(created.lease_transitions, Some(0));
+        assert_eq!(created.lease_duration_seconds, Some(15));
+    }
+
+    #[test]
+    fn test_only_holder_releases_lease() {
+        assert!(released(Some(&held_by("a", 0)), "b", Utc::now()).is_none());
+        assert!(released(None, "a", Utc::now()).is_none());
+    }
+
+    #[test]
+    fn test_renew_deadline_must_be_between_retry_period_and_lease_duration() {
+        let config = |lease_duration, renew_deadline, retry_period| LeaseConfig {
+            lease_duration: Duration::from_secs(lease_duration),
+            renew_deadline: Duration::from_secs(renew_deadline),
+            retry_period: Duration::from_secs(retry_period),
+        };
+
+        assert!(config(15, 10, 2).validate().is_ok());
+        assert!(config(15, 2, 2).validate().is_err());
+        assert!(config(15, 1, 2).validate().is_err());
+        assert!(config(15, 15, 2).validate().is_err());
+        assert!(config(10, 15, 2).validate().is_err());
+    }
+}
@@ -1 +1,2 @@
 pub mod leader_election;
+mod leader_election_tests;
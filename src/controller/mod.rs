@@ -3,5 +3,5 @@ pub mod leader_election;
 pub mod utils;
 pub mod v1alpha1;
 
-mod config_store;
+pub mod config_store;
 pub mod controller_data;
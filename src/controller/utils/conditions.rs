@@ -0,0 +1,37 @@
+use chrono::Utc;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{Condition, Time};
+
+pub static CONDITION_READY: &str = "Ready";
+pub static CONDITION_DEGRADED: &str = "Degraded";
+pub static CONDITION_SYNCED: &str = "Synced";
+
+pub fn condition(
+    type_: &str,
+    status: bool,
+    reason: &str,
+    message: &str,
+    observed_generation: Option<i64>,
+) -> Condition {
+    Condition {
+        type_: type_.to_string(),
+        status: String::from(if status { "True" } else { "False" }),
+        reason: reason.to_string(),
+        message: message.to_string(),
+        observed_generation,
+        last_transition_time: Time(Utc::now()),
+    }
+}
+
+/// Replaces condition of the same type. Transition time is kept when status did not change,
+/// so it tells since when the condition holds rather than when it was last checked.
+pub fn set_condition(conditions: &mut Vec<Condition>, mut condition: Condition) {
+    match conditions.iter_mut().find(|c| c.type_ == condition.type_) {
+        Some(existing) => {
+            if existing.status == condition.status {
+                condition.last_transition_time = existing.last_transition_time.clone();
+            }
+            *existing = condition;
+        }
+        None => conditions.push(condition),
+    }
+}
@@ -1,29 +1,62 @@
-
 use std::env;
+use std::time::Duration;
 
 pub struct Config {}
 
 impl Config {
-    pub fn kubernetes_namespace() -> Option<String>{
-        env::var("KUBERNETES_NAMESPACE").map(|v| Some(v)).unwrap_or(None)
+    pub fn kubernetes_namespace() -> Option<String> {
+        env::var("KUBERNETES_NAMESPACE")
+            .map(|v| Some(v))
+            .unwrap_or(None)
     }
-    pub fn kubernetes_pod_name() -> Option<String>{
-        env::var("KUBERNETES_POD_NAME").map(|v| Some(v)).unwrap_or(None)
+    pub fn kubernetes_pod_name() -> Option<String> {
+        env::var("KUBERNETES_POD_NAME")
+            .map(|v| Some(v))
+            .unwrap_or(None)
     }
     pub fn is_in_pod() -> bool {
         env::var("KUBERNETES_POD_NAME")
             .map(|x| true)
             .unwrap_or_else(|_| false)
     }
-    pub fn enable_leader_election() -> bool{
+    pub fn enable_leader_election() -> bool {
         env::var("ENABLE_LEADER_ELECTION")
             .map(|x| matches!(x.to_ascii_lowercase().as_str(), "1" | "true"))
             .unwrap_or(false)
     }
-    pub fn opentelemetry_endpoint_url() -> Option<String>{
-        env::var("OPENTELEMETRY_ENDPOINT_URL").map(|v| Some(v)).unwrap_or(None)
+    pub fn opentelemetry_endpoint_url() -> Option<String> {
+        env::var("OPENTELEMETRY_ENDPOINT_URL")
+            .map(|v| Some(v))
+            .unwrap_or(None)
+    }
+    /// Secret signing store webhook requests, webhook is disabled when not set
+    pub fn webhook_secret() -> Option<String> {
+        env::var("WEBHOOK_SECRET").ok().filter(|v| !v.is_empty())
+    }
+    /// Minimal interval between accepted notifications of the same store
+    pub fn webhook_min_interval() -> Duration {
+        Self::seconds_var("WEBHOOK_MIN_INTERVAL_SECONDS", 10)
+    }
+    /// How long lease is valid after last renewal, before standby replica may take it over
+    pub fn leader_election_lease_duration() -> Duration {
+        Self::seconds_var("LEADER_ELECTION_LEASE_DURATION_SECONDS", 15)
+    }
+    /// How long leader retries renewing lease, before it stops controllers
+    pub fn leader_election_renew_deadline() -> Duration {
+        Self::seconds_var("LEADER_ELECTION_RENEW_DEADLINE_SECONDS", 10)
+    }
+    /// Interval between attempts to acquire or renew lease
+    pub fn leader_election_retry_period() -> Duration {
+        Self::seconds_var("LEADER_ELECTION_RETRY_PERIOD_SECONDS", 2)
+    }
+    fn seconds_var(name: &str, default: u64) -> Duration {
+        env::var(name)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(default))
     }
     pub fn new() -> Self {
         Config {}
     }
-}
\ No newline at end of file
+}
@@ -1,11 +1,14 @@
 use crate::contract::clients::K8sClient;
+use crate::controller::config_store::content_cache::ParsedContentCache;
+use crate::controller::config_store::store_registry::ConfigStoreRegistry;
+use crate::controller::utils::config::Config;
 use crate::controller::v1alpha1;
+use crate::controller::v1alpha1::claim_triggers::{ResyncRequests, StoreChanges};
 use crate::observability::metrics::Metrics;
 use kube::Client;
 use std::cell::OnceCell;
 use std::sync::Arc;
 use tokio::sync::Notify;
-use crate::controller::utils::config::Config;
 
 pub struct ConfigurationManager {}
 
@@ -40,4 +43,8 @@ pub struct Context {
     pub v1alpha1: Arc<v1alpha1::crd_client::CrdClient>,
     pub api_client: Arc<v1alpha1::crd_client::CrdClient>,
     pub metrics: Arc<Metrics>,
+    pub config_stores: Arc<ConfigStoreRegistry>,
+    pub parsed_content: Arc<ParsedContentCache>,
+    pub claim_resync: Arc<ResyncRequests>,
+    pub store_changes: Arc<StoreChanges>,
 }
@@ -1,21 +1,34 @@
 use chrono::Duration;
 use k8s_openapi::api::core::v1::{ConfigMap, Secret};
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
-use schemars::schema::{InstanceType, Metadata, Schema, SchemaObject};
+use k8s_openapi::ByteString;
+use schemars::schema::{InstanceType, Metadata, Schema, SchemaObject, StringValidation};
 use schemars::JsonSchema;
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::BTreeMap;
 use std::str::FromStr;
+/// Duration written in human readable form, e.g. `15m` or `500ms`.
 #[derive(Debug, Clone, PartialEq)]
-pub struct RefreshInterval(Duration);
+pub struct HumanDuration(Duration);
 
-impl RefreshInterval {
+pub type RefreshInterval = HumanDuration;
+
+/// Durations accepted by `humantime`, e.g. `1h 30m`, `15m` or `500ms`. Validated by API server,
+/// so malformed intervals are rejected on apply rather than failing reconcile.
+pub static HUMAN_DURATION_PATTERN: &str = r"^\s*([0-9]+\s*(nsec|ns|usec|us|msec|ms|seconds|second|sec|s|minutes|minute|min|m|hours|hour|hr|h|days|day|d|weeks|week|w|months|month|M|years|year|y)\s*)+$";
+
+impl HumanDuration {
     /// Returns the wrapped `Duration` object.
     pub fn as_duration(&self) -> Duration {
         self.0
     }
 
+    /// Returns the duration as `std::time::Duration`, negative durations are clamped to zero.
+    pub fn as_std(&self) -> std::time::Duration {
+        self.0.to_std().unwrap_or_default()
+    }
+
     /// Returns the duration as the number of seconds.
     pub fn as_seconds(&self) -> u64 {
         if self.0.num_seconds() < 0 {
@@ -34,20 +47,20 @@ impl RefreshInterval {
         }
     }
 }
-impl FromStr for RefreshInterval {
-    type Err = humantime::DurationError;
+impl FromStr for HumanDuration {
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match humantime::parse_duration(s) {
-            Ok(d) => Ok(RefreshInterval(Duration::from_std(d).unwrap())),
-            Err(e) => Err(e), // Directly return the humantime error
-        }
+        let duration = humantime::parse_duration(s).map_err(|e| e.to_string())?;
+        Duration::from_std(duration)
+            .map(HumanDuration)
+            .map_err(|_| format!("duration {} is out of range", s))
     }
 }
 
-impl JsonSchema for RefreshInterval {
+impl JsonSchema for HumanDuration {
     fn schema_name() -> String {
-        "RefreshInterval".to_string()
+        "HumanDuration".to_string()
     }
 
     fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> Schema {
@@ -57,23 +70,44 @@ impl JsonSchema for RefreshInterval {
                 description: Some("A time duration like '1h', '15m', '2600s'".to_string()),
                 ..Default::default()
             })),
+            string: Some(Box::new(StringValidation {
+                pattern: Some(HUMAN_DURATION_PATTERN.to_string()),
+                ..Default::default()
+            })),
             ..Default::default()
         }
         .into()
     }
 }
 
-impl Serialize for RefreshInterval {
+/// Schema of arbitrary JSON value, kept by API server as is.
+pub fn preserve_unknown_fields(_: &mut schemars::gen::SchemaGenerator) -> Schema {
+    SchemaObject {
+        extensions: [(
+            "x-kubernetes-preserve-unknown-fields".to_string(),
+            serde_json::Value::Bool(true),
+        )]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    }
+    .into()
+}
+
+impl Serialize for HumanDuration {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let duration_str = humantime::format_duration(self.0.to_std().unwrap()).to_string();
-        serializer.serialize_str(&duration_str)
+        let duration = self
+            .0
+            .to_std()
+            .map_err(|_| serde::ser::Error::custom("duration must not be negative"))?;
+        serializer.serialize_str(&humantime::format_duration(duration).to_string())
     }
 }
 
-impl<'de> Deserialize<'de> for RefreshInterval {
+impl<'de> Deserialize<'de> for HumanDuration {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
@@ -85,6 +119,8 @@ impl<'de> Deserialize<'de> for RefreshInterval {
 
 pub trait HasData {
     fn get_data(&self) -> Option<BTreeMap<String, String>>;
+    /// Data compared byte for byte, `binaryData` of ConfigMap or raw data of Secret.
+    fn get_binary_data(&self) -> Option<BTreeMap<String, ByteString>>;
     fn get_metadata_mut(&mut self) -> &mut ObjectMeta;
 }
 
@@ -92,6 +128,9 @@ impl HasData for ConfigMap {
     fn get_data(&self) -> Option<BTreeMap<String, String>> {
         self.data.clone()
     }
+    fn get_binary_data(&self) -> Option<BTreeMap<String, ByteString>> {
+        self.binary_data.clone()
+    }
     fn get_metadata_mut(&mut self) -> &mut ObjectMeta {
         &mut self.metadata
     }
@@ -109,6 +148,10 @@ impl HasData for Secret {
         })
     }
 
+    fn get_binary_data(&self) -> Option<BTreeMap<String, ByteString>> {
+        self.data.clone()
+    }
+
     fn get_metadata_mut(&mut self) -> &mut ObjectMeta {
         &mut self.metadata
     }
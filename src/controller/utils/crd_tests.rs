@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use crate::controller::utils::crd::{HumanDuration, HUMAN_DURATION_PATTERN};
+    use crate::controller::v1alpha1::controller::crds;
+    use regex::Regex;
+    use serde_json::json;
+
+    #[test]
+    fn test_duration_pattern_accepts_what_humantime_parses() {
+        let pattern = Regex::new(HUMAN_DURATION_PATTERN).unwrap();
+
+        for valid in ["5m", "1h 30m", "1h30m", "500ms", "2600s", "1day", "2 weeks"] {
+            assert!(valid.parse::<HumanDuration>().is_ok(), "{valid}");
+            assert!(pattern.is_match(valid), "{valid} should match");
+        }
+        for invalid in ["", "5", "m", "5 parsecs", "-5m", "1.5h"] {
+            assert!(invalid.parse::<HumanDuration>().is_err(), "{invalid}");
+            assert!(!pattern.is_match(invalid), "{invalid} should not match");
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_duration_is_rejected() {
+        let error = serde_json::from_value::<HumanDuration>(json!("100000000000y")).unwrap_err();
+        assert!(error.to_string().contains("out of range"), "{error}");
+
+        let duration: HumanDuration = serde_json::from_value(json!("1h 30m")).unwrap();
+        assert_eq!(serde_json::to_value(&duration).unwrap(), json!("1h 30m"));
+    }
+
+    #[test]
+    fn test_all_crds_share_category_and_define_printer_columns() {
+        for crd in crds() {
+            assert_eq!(
+                crd.spec.names.categories,
+                Some(vec![String::from("externalconfig")]),
+                "{}",
+                crd.spec.names.kind
+            );
+            let columns = crd.spec.versions[0]
+                .additional_printer_columns
+                .clone()
+                .unwrap_or_default();
+            assert!(
+                columns.iter().any(|column| column.name == "Ready"),
+                "{}",
+                crd.spec.names.kind
+            );
+        }
+    }
+
+    #[test]
+    fn test_vault_kubernetes_auth_does_not_accept_token_path() {
+        for crd in crds() {
+            let schema = serde_json::to_string(&crd).unwrap();
+            assert!(!schema.contains("tokenPath"), "{}", crd.spec.names.kind);
+        }
+    }
+
+    #[test]
+    fn test_store_provider_columns_do_not_depend_on_probe() {
+        for crd in crds()
+            .into_iter()
+            .filter(|crd| crd.spec.names.kind.ends_with("ConfigurationStore"))
+        {
+            let columns = crd.spec.versions[0]
+                .additional_printer_columns
+                .clone()
+                .unwrap_or_default();
+            for name in ["HTTP", "Vault"] {
+                let column = columns.iter().find(|column| column.name == name).unwrap();
+                assert!(column.json_path.starts_with(".spec.provider."), "{}", name);
+            }
+            let store_type = columns
+                .iter()
+                .find(|column| column.name == "Store Type")
+                .unwrap();
+            assert_eq!(store_type.json_path, ".status.storeType");
+        }
+    }
+}
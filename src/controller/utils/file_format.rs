@@ -1,4 +1,5 @@
 use crate::contract::lib::Error;
+use crate::controller::utils::crd::preserve_unknown_fields;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -27,6 +28,7 @@ pub enum ConfigFileType {
     EnvFile,
     Properties,
 }
+#[derive(Clone)]
 pub enum ConfigFormat {
     Json(JsonValue),
     Toml(TomlValue),
@@ -35,14 +37,111 @@ pub enum ConfigFormat {
     EnvFile(HashMap<String, String>),
 }
 
+/// How arrays present in both merged documents are combined.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub enum ArrayMergeStrategy {
+    /// Later array replaces earlier one
+    Replace,
+    /// Items of later array are appended
+    Append,
+    /// Items with equal merge key are merged, others are appended
+    MergeByKey,
+}
+
+/// Which document wins when both hold a value under the same key.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub enum MergePrecedence {
+    /// Later store overrides earlier ones
+    Overwrite,
+    /// First store serving a value keeps it, later ones only add missing keys
+    KeepFirst,
+}
+
+/// Deep merge semantics of `Merge` strategy.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeOptions {
+    /// Defaults to `Replace`
+    pub arrays: Option<ArrayMergeStrategy>,
+    /// Field identifying items of arrays merged with `MergeByKey`. Defaults to `name`
+    pub merge_key: Option<String>,
+    /// `null` in later document deletes the key, as in JSON Merge Patch. Defaults to `false`
+    pub delete_nulls: Option<bool>,
+    /// Defaults to `Overwrite`
+    pub precedence: Option<MergePrecedence>,
+    /// RFC 6902 JSON Patch applied to merged document, e.g. to override single value
+    pub patch: Option<Vec<JsonPatchOperation>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonPatchOp {
+    Add,
+    Remove,
+    Replace,
+    Move,
+    Copy,
+    Test,
+}
+
+/// Single RFC 6902 operation, e.g. `{op: replace, path: /server/port, value: 8080}`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct JsonPatchOperation {
+    pub op: JsonPatchOp,
+    /// JSON Pointer of the target location
+    pub path: String,
+    /// Source location of `move` and `copy`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Value of `add`, `replace` and `test`, `null` included
+    #[serde(
+        default,
+        deserialize_with = "present_value",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(schema_with = "nullable_unknown_fields")]
+    pub value: Option<JsonValue>,
+}
+
+/// Keeps explicit `null` as value, only missing field is `None`.
+fn present_value<'de, D>(deserializer: D) -> Result<Option<JsonValue>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    JsonValue::deserialize(deserializer).map(Some)
+}
+
+/// Any value including `null`, which API server would otherwise drop from the field.
+fn nullable_unknown_fields(
+    generator: &mut schemars::gen::SchemaGenerator,
+) -> schemars::schema::Schema {
+    let mut schema = preserve_unknown_fields(generator).into_object();
+    schema
+        .extensions
+        .insert("nullable".to_string(), JsonValue::Bool(true));
+    schema.into()
+}
+
 /// Merges two structured configurations together.
 /// Merges two JSON configurations together.
 /// Merges two structured configurations together.
 pub fn merge_configs(config1: ConfigFormat, config2: ConfigFormat) -> Result<ConfigFormat, Error> {
+    merge_configs_with(config1, config2, &MergeOptions::default())
+}
+
+/// Merges two configurations with given merge semantics. Options apply to JSON documents,
+/// which every fetched document is converted to.
+pub fn merge_configs_with(
+    config1: ConfigFormat,
+    config2: ConfigFormat,
+    options: &MergeOptions,
+) -> Result<ConfigFormat, Error> {
     match (config1, config2) {
         // Merging two JSON configurations
         (ConfigFormat::Json(mut json1), ConfigFormat::Json(json2)) => {
-            merge_json(&mut json1, &json2);
+            if json1.is_object() && json2.is_object() {
+                merge_json_with(&mut json1, &json2, options);
+            }
             Ok(ConfigFormat::Json(json1))
         }
         // Merging two TOML configurations
@@ -100,6 +199,21 @@ pub fn to_file_type_from_filename(filename: &str) -> Option<ConfigFileType> {
     }
 }
 
+/// Format hinted by media type of store response, e.g. `application/yaml; charset=utf-8`.
+pub fn to_file_type_from_content_type(content_type: &str) -> Option<ConfigFileType> {
+    let media_type = content_type.split(';').next()?.trim().to_ascii_lowercase();
+    match media_type.as_str() {
+        "application/json" => Some(ConfigFileType::Json),
+        "application/json5" => Some(ConfigFileType::Json5),
+        "application/toml" => Some(ConfigFileType::Toml),
+        "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => {
+            Some(ConfigFileType::Yaml)
+        }
+        "text/x-java-properties" | "text/x-properties" => Some(ConfigFileType::Properties),
+        _ => None,
+    }
+}
+
 /// Converts a structured configuration format back to its string representation.
 pub fn convert_to_format(
     config: &ConfigFormat,
@@ -188,22 +302,73 @@ pub fn convert_to_json(config: &ConfigFormat) -> Result<ConfigFormat, Error> {
 }
 /// Helper functions to merge JSON, TOML, YAML configurations.
 
-/// Helper function to merge JSON objects.
-fn merge_json(target: &mut JsonValue, source: &JsonValue) {
-    if let (Some(target_map), Some(source_map)) = (target.as_object_mut(), source.as_object()) {
-        for (key, value) in source_map {
-            match target_map.get_mut(key) {
-                Some(target_value) if target_value.is_object() && value.is_object() => {
-                    merge_json(target_value, value);
+/// Helper function to merge JSON values. Objects are merged recursively, arrays according
+/// to options, other values are replaced unless first value is kept.
+fn merge_json_with(target: &mut JsonValue, source: &JsonValue, options: &MergeOptions) {
+    let keep_first = options.precedence == Some(MergePrecedence::KeepFirst);
+    match (target, source) {
+        (JsonValue::Object(target_map), JsonValue::Object(source_map)) => {
+            for (key, value) in source_map {
+                if value.is_null() && options.delete_nulls.unwrap_or(false) {
+                    target_map.remove(key);
+                    continue;
                 }
-                _ => {
-                    target_map.insert(key.clone(), value.clone());
+                match target_map.get_mut(key) {
+                    Some(target_value)
+                        if (target_value.is_object() && value.is_object())
+                            || (target_value.is_array() && value.is_array()) =>
+                    {
+                        merge_json_with(target_value, value, options);
+                    }
+                    Some(_) if keep_first => {}
+                    _ => {
+                        target_map.insert(key.clone(), value.clone());
+                    }
                 }
             }
         }
+        (JsonValue::Array(target_items), JsonValue::Array(source_items)) => {
+            match options
+                .arrays
+                .as_ref()
+                .unwrap_or(&ArrayMergeStrategy::Replace)
+            {
+                ArrayMergeStrategy::Replace if !keep_first => {
+                    target_items.clone_from(source_items);
+                }
+                ArrayMergeStrategy::Replace => {}
+                ArrayMergeStrategy::Append => target_items.extend(source_items.iter().cloned()),
+                ArrayMergeStrategy::MergeByKey => {
+                    let merge_key = options.merge_key.as_deref().unwrap_or("name");
+                    for item in source_items {
+                        let existing = item.get(merge_key).and_then(|key| {
+                            target_items
+                                .iter_mut()
+                                .find(|target_item| target_item.get(merge_key) == Some(key))
+                        });
+                        match existing {
+                            Some(existing) => merge_json_with(existing, item, options),
+                            None => target_items.push(item.clone()),
+                        }
+                    }
+                }
+            }
+        }
+        _ => {}
     }
 }
 
+/// Applies RFC 6902 JSON Patch. Document is left untouched when any operation fails.
+pub fn apply_json_patch(
+    document: &mut JsonValue,
+    operations: &[JsonPatchOperation],
+) -> Result<(), Error> {
+    let patch: json_patch::Patch = serde_json::to_value(operations)
+        .and_then(serde_json::from_value)
+        .map_err(|e| Error::JsonPatchError(e.to_string()))?;
+    json_patch::patch(document, &patch).map_err(|e| Error::JsonPatchError(e.to_string()))
+}
+
 /// Helper function to merge TOML values.
 fn merge_toml(target: &mut TomlValue, source: &TomlValue) {
     if let (Some(target_table), Some(source_table)) = (target.as_table_mut(), source.as_table()) {
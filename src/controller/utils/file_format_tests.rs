@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::lib::Error;
+    use crate::controller::utils::file_format::{
+        apply_json_patch, merge_configs, merge_configs_with, ArrayMergeStrategy, ConfigFormat,
+        JsonPatchOperation, MergeOptions, MergePrecedence,
+    };
+    use serde_json::{json, Value};
+
+    fn merged(first: Value, second: Value, options: MergeOptions) -> Value {
+        match merge_configs_with(
+            ConfigFormat::Json(first),
+            ConfigFormat::Json(second),
+            &options,
+        ) {
+            Ok(ConfigFormat::Json(json)) => json,
+            _ => panic!("Expected merged JSON document"),
+        }
+    }
+
+    fn arrays(strategy: ArrayMergeStrategy) -> MergeOptions {
+        MergeOptions {
+            arrays: Some(strategy),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_merge_replaces_arrays_and_scalars() {
+        let result = merge_configs(
+            ConfigFormat::Json(json!({ "a": { "b": 1, "c": [1, 2] }, "d": null })),
+            ConfigFormat::Json(json!({ "a": { "b": 2, "c": [3] }, "d": null })),
+        );
+        match result {
+            Ok(ConfigFormat::Json(json)) => {
+                assert_eq!(json, json!({ "a": { "b": 2, "c": [3] }, "d": null }))
+            }
+            _ => panic!("Expected merged JSON document"),
+        }
+    }
+
+    #[test]
+    fn test_array_strategies() {
+        let first = json!({ "hosts": ["a"], "users": [{ "name": "admin", "role": "ro" }] });
+        let second = json!({ "hosts": ["b"], "users": [{ "name": "admin", "role": "rw" }, { "name": "app" }] });
+
+        assert_eq!(
+            merged(
+                first.clone(),
+                second.clone(),
+                arrays(ArrayMergeStrategy::Append)
+            )["hosts"],
+            json!(["a", "b"])
+        );
+        assert_eq!(
+            merged(
+                first.clone(),
+                second.clone(),
+                arrays(ArrayMergeStrategy::MergeByKey)
+            )["users"],
+            json!([{ "name": "admin", "role": "rw" }, { "name": "app" }])
+        );
+
+        let by_id = MergeOptions {
+            merge_key: Some("id".to_string()),
+            ..arrays(ArrayMergeStrategy::MergeByKey)
+        };
+        assert_eq!(
+            merged(
+                json!({ "a": [{ "id": 1, "x": 1 }] }),
+                json!({ "a": [{ "id": 1, "y": 2 }] }),
+                by_id
+            ),
+            json!({ "a": [{ "id": 1, "x": 1, "y": 2 }] })
+        );
+    }
+
+    #[test]
+    fn test_null_deletes_key_and_keep_first_precedence() {
+        let delete_nulls = MergeOptions {
+            delete_nulls: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            merged(
+                json!({ "a": { "b": 1, "c": 2 } }),
+                json!({ "a": { "b": null } }),
+                delete_nulls
+            ),
+            json!({ "a": { "c": 2 } })
+        );
+
+        let keep_first = MergeOptions {
+            precedence: Some(MergePrecedence::KeepFirst),
+            ..Default::default()
+        };
+        assert_eq!(
+            merged(
+                json!({ "a": { "b": 1 }, "hosts": ["a"] }),
+                json!({ "a": { "b": 2, "c": 3 }, "hosts": ["b"] }),
+                keep_first
+            ),
+            json!({ "a": { "b": 1, "c": 3 }, "hosts": ["a"] })
+        );
+    }
+
+    #[test]
+    fn test_json_patch_is_applied_atomically() {
+        let operations: Vec<JsonPatchOperation> = serde_json::from_value(json!([
+            { "op": "replace", "path": "/server/port", "value": 8080 },
+            { "op": "add", "path": "/hosts/-", "value": "c" },
+            { "op": "remove", "path": "/debug" }
+        ]))
+        .unwrap();
+        let mut document = json!({ "server": { "port": 80 }, "hosts": ["a"], "debug": true });
+
+        apply_json_patch(&mut document, &operations).unwrap();
+        assert_eq!(
+            document,
+            json!({ "server": { "port": 8080 }, "hosts": ["a", "c"] })
+        );
+
+        let failing: Vec<JsonPatchOperation> = serde_json::from_value(json!([
+            { "op": "replace", "path": "/server/port", "value": 1 },
+            { "op": "test", "path": "/hosts/0", "value": "x" }
+        ]))
+        .unwrap();
+        let error = apply_json_patch(&mut document, &failing).unwrap_err();
+        assert!(matches!(error, Error::JsonPatchError(_)));
+        assert_eq!(document["server"]["port"], 8080);
+    }
+
+    #[test]
+    fn test_json_patch_replaces_value_with_null() {
+        let operations: Vec<JsonPatchOperation> = serde_json::from_value(json!([
+            { "op": "replace", "path": "/server/port", "value": null },
+            { "op": "add", "path": "/server/host", "value": null }
+        ]))
+        .unwrap();
+        let mut document = json!({ "server": { "port": 80 } });
+
+        apply_json_patch(&mut document, &operations).unwrap();
+        assert_eq!(
+            document,
+            json!({ "server": { "port": null, "host": null } })
+        );
+
+        let missing_value: Vec<JsonPatchOperation> =
+            serde_json::from_value(json!([{ "op": "replace", "path": "/server/port" }])).unwrap();
+        let error = apply_json_patch(&mut document, &missing_value).unwrap_err();
+        assert!(matches!(error, Error::JsonPatchError(_)));
+    }
+}
@@ -1,6 +1,15 @@
+pub mod conditions;
+pub mod config;
 pub mod context;
 pub mod crd;
+mod crd_tests;
 pub mod file_format;
+mod file_format_tests;
 pub mod parsers;
+pub mod selector;
+mod selector_tests;
 pub mod signals;
-pub mod config;
+pub mod template;
+mod template_tests;
+pub mod webhook;
+mod webhook_tests;
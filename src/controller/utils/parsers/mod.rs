@@ -1,3 +1,4 @@
 pub mod json_to_key_value;
 pub mod key_value_to_json;
 pub mod text_to_json;
+mod text_to_json_tests;
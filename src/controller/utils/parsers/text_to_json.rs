@@ -1,89 +1,186 @@
 use crate::contract::lib::Error;
-use crate::controller::utils::file_format::ConfigFormat;
+use crate::controller::utils::file_format::{ConfigFileType, ConfigFormat};
 use crate::controller::utils::parsers::key_value_to_json;
 use convert_case::Case;
 use env_file_reader::read_str;
 use serde_json::Value as JsonValue;
 use serde_yaml::Value as YamlValue;
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::io::Cursor;
 use toml::Value as TomlValue;
 
 /// Parses `.properties` files using `java-properties` and converts them into a nested JSON structure.
 fn try_parse_from_properties(content: &str) -> Result<ConfigFormat, Error> {
+    parse_properties(content).map(ConfigFormat::Json)
+}
+
+fn parse_properties(content: &str) -> Result<JsonValue, Error> {
     let reader = Cursor::new(content);
 
     // Use `java-properties` to read key-value pairs into a HashMap
-    let properties: HashMap<String, String> =
-        java_properties::read(reader).map_err(|e| Error::PropertiesSerializationError(e))?;
+    let properties: HashMap<String, String> = java_properties::read(reader).map_err(|e| {
+        let line = e.line_number();
+        parse_error(ConfigFileType::Properties, e, line)
+    })?;
 
     let transforms = [];
     let map = key_value_to_json::key_value_pairs_to_json(properties, '.', None, &transforms);
 
-    Ok(ConfigFormat::Json(JsonValue::Object(map)))
+    Ok(JsonValue::Object(map))
 }
 
 // Function to parse the env file and generate a nested JSON structure
 fn try_parse_from_env(content: &str) -> Result<ConfigFormat, Error> {
-    let reader = Cursor::new(content);
+    parse_env(content).map(ConfigFormat::Json)
+}
 
+fn parse_env(content: &str) -> Result<JsonValue, Error> {
     // Use `env_file_reader` to read key-value pairs into a HashMap
-    let env_vars: HashMap<String, String> =
-        read_str(content).map_err(|e| Error::EnvFileSerializationError(e))?;
+    let env_vars: HashMap<String, String> = read_str(content).map_err(|e| {
+        // Parser does not report position, first line failing on its own is the culprit
+        let line = content
+            .lines()
+            .position(|line| read_str(line).is_err())
+            .map(|index| index + 1);
+        parse_error(ConfigFileType::EnvFile, e, line)
+    })?;
 
     let transforms = [Case::Lower, Case::Camel];
     let map = key_value_to_json::key_value_pairs_to_json(env_vars, '_', Some("_"), &transforms);
 
-    Ok(ConfigFormat::Json(JsonValue::Object(map)))
+    Ok(JsonValue::Object(map))
 }
 
 /// Tries to parse the configuration file content into a JSON structure.
 fn try_parse_from_json(content: &str) -> Result<ConfigFormat, Error> {
-    let json: JsonValue =
-        serde_json::from_str::<JsonValue>(content).map_err(|_| Error::ParseError())?;
-    if !json.is_object() {
-        return Err(Error::ParseError());
-    }
-    Ok(ConfigFormat::Json(json))
+    parse_json(content).and_then(|json| document_object(ConfigFileType::Json, json))
+}
+
+fn parse_json(content: &str) -> Result<JsonValue, Error> {
+    serde_json::from_str::<JsonValue>(content).map_err(|e| {
+        let line = e.line();
+        parse_error(ConfigFileType::Json, e, Some(line))
+    })
 }
 
 fn try_parse_from_json5(content: &str) -> Result<ConfigFormat, Error> {
-    let json: JsonValue = json5::from_str::<JsonValue>(content)
-        .map(serde_json::Value::from)
-        .map_err(|_| Error::ParseError())?;
-    if !json.is_object() {
-        return Err(Error::ParseError());
-    }
-    Ok(ConfigFormat::Json(json))
+    parse_json5(content).and_then(|json| document_object(ConfigFileType::Json5, json))
+}
+
+fn parse_json5(content: &str) -> Result<JsonValue, Error> {
+    json5::from_str::<JsonValue>(content).map_err(|e| {
+        let json5::Error::Message { location, .. } = &e;
+        let line = location.as_ref().map(|location| location.line);
+        parse_error(ConfigFileType::Json5, e, line)
+    })
 }
 
 /// Tries to parse the configuration file content as TOML.
 fn try_parse_from_toml(content: &str) -> Result<ConfigFormat, Error> {
-    let toml_val: TomlValue = toml::from_str(content).map_err(|_| Error::ParseError())?;
-    let json = serde_json::to_value(toml_val).unwrap(); // Convert TOML to JSON
-    if !json.is_object() {
-        return Err(Error::ParseError());
-    }
-    Ok(ConfigFormat::Json(json))
+    parse_toml(content).and_then(|json| document_object(ConfigFileType::Toml, json))
+}
+
+fn parse_toml(content: &str) -> Result<JsonValue, Error> {
+    let toml_val: TomlValue = toml::from_str(content).map_err(|e| {
+        let line = e.span().map(|span| line_at(content, span.start));
+        parse_error(ConfigFileType::Toml, e.message(), line)
+    })?;
+    Ok(serde_json::to_value(toml_val).unwrap()) // Convert TOML to JSON
 }
 
 /// Tries to parse the configuration file content as YAML.
 fn try_parse_from_yaml(content: &str) -> Result<ConfigFormat, Error> {
-    let yaml_val: YamlValue = serde_yaml::from_str(content).map_err(|_| Error::ParseError())?;
-    let json = serde_json::to_value(yaml_val).unwrap(); // Convert YAML to JSON
+    parse_yaml(content).and_then(|json| document_object(ConfigFileType::Yaml, json))
+}
+
+fn parse_yaml(content: &str) -> Result<JsonValue, Error> {
+    let yaml_val: YamlValue = serde_yaml::from_str(content).map_err(|e| {
+        let line = e.location().map(|location| location.line());
+        parse_error(ConfigFileType::Yaml, e, line)
+    })?;
+    Ok(serde_json::to_value(yaml_val).unwrap()) // Convert YAML to JSON
+}
+
+/// Any text is valid YAML plain scalar, so only structured YAML is recognized by the chain.
+fn parse_structured_yaml(content: &str) -> Result<JsonValue, Error> {
+    parse_yaml(content).and_then(|json| match json {
+        JsonValue::String(_) => Err(parse_error(
+            ConfigFileType::Yaml,
+            "document is plain text",
+            None,
+        )),
+        json => Ok(json),
+    })
+}
+
+/// Configuration document has to be an object, scalars and lists are rejected.
+fn document_object(format: ConfigFileType, json: JsonValue) -> Result<ConfigFormat, Error> {
     if !json.is_object() {
-        return Err(Error::ParseError());
+        return Err(parse_error(format, "document is not an object", None));
     }
     Ok(ConfigFormat::Json(json))
 }
 
-/// Chain of parsers. Each parser tries to parse the content until one succeeds.
+fn parse_error(format: ConfigFileType, error: impl Display, line: Option<usize>) -> Error {
+    let message = match line {
+        Some(line) => format!("{} at line {}", error, line),
+        None => error.to_string(),
+    };
+    Error::FormatParseError(format!("{:?}", format), message)
+}
+
+/// One-based line of byte offset in content.
+fn line_at(content: &str, offset: usize) -> usize {
+    content
+        .get(..offset)
+        .map_or(1, |before| before.matches('\n').count() + 1)
+}
+
+type DocumentParser = fn(&str) -> Result<JsonValue, Error>;
+
+/// Chain of parsers. Each parser tries to parse the content until one succeeds. Content
+/// recognized by a parser has to be an object, later formats would only misread it.
 pub fn try_parse_file_to_json(content: &str) -> Result<ConfigFormat, Error> {
-    try_parse_from_json(content)
-        .or_else(|_| try_parse_from_json5(content))
-        .or_else(|_| try_parse_from_env(content))
-        .or_else(|_| try_parse_from_toml(content))
-        .or_else(|_| try_parse_from_yaml(content))
-        .or_else(|_| try_parse_from_properties(content))
-        .map_err(|_| Error::UnsupportedFileType())
+    let parsers: [(ConfigFileType, DocumentParser); 6] = [
+        (ConfigFileType::Json, parse_json),
+        (ConfigFileType::Json5, parse_json5),
+        (ConfigFileType::EnvFile, parse_env),
+        (ConfigFileType::Toml, parse_toml),
+        (ConfigFileType::Yaml, parse_structured_yaml),
+        (ConfigFileType::Properties, parse_properties),
+    ];
+    parsers
+        .into_iter()
+        .find_map(|(format, parse)| parse(content).ok().map(|json| (format, json)))
+        .map_or(Err(Error::UnsupportedFileType()), |(format, json)| {
+            document_object(format, json)
+        })
+}
+
+/// Parses content in given format only, error tells which format failed and where.
+pub fn parse_file_to_json(content: &str, format: &ConfigFileType) -> Result<ConfigFormat, Error> {
+    match format {
+        ConfigFileType::Json => try_parse_from_json(content),
+        ConfigFileType::Json5 => try_parse_from_json5(content),
+        ConfigFileType::Toml => try_parse_from_toml(content),
+        ConfigFileType::Yaml => try_parse_from_yaml(content),
+        ConfigFileType::EnvFile => try_parse_from_env(content),
+        ConfigFileType::Properties => try_parse_from_properties(content),
+    }
+}
+
+/// Parses store payload in explicit input format. Without one, format hinted by store,
+/// e.g. by `Content-Type`, is tried first and the parser chain is the last resort.
+pub fn parse_file(
+    content: &str,
+    input_format: Option<&ConfigFileType>,
+    hint: Option<&ConfigFileType>,
+) -> Result<ConfigFormat, Error> {
+    match (input_format, hint) {
+        (Some(format), _) => parse_file_to_json(content, format),
+        (None, Some(hint)) => parse_file_to_json(content, hint)
+            .or_else(|e| try_parse_file_to_json(content).map_err(|_| e)),
+        (None, None) => try_parse_file_to_json(content),
+    }
 }
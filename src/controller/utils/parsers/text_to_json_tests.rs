@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::lib::Error;
+    use crate::controller::utils::file_format::{
+        to_file_type_from_content_type, ConfigFileType, ConfigFormat,
+    };
+    use crate::controller::utils::parsers::text_to_json::{
+        parse_file, parse_file_to_json, try_parse_file_to_json,
+    };
+    use serde_json::json;
+
+    fn parsed(result: Result<ConfigFormat, Error>) -> serde_json::Value {
+        match result {
+            Ok(ConfigFormat::Json(json)) => json,
+            Ok(_) => panic!("Expected JSON document"),
+            Err(e) => panic!("Expected document to be parsed: {}", e),
+        }
+    }
+
+    fn error_message(result: Result<ConfigFormat, Error>) -> String {
+        match result {
+            Err(e) => e.to_string(),
+            Ok(_) => panic!("Expected parse error"),
+        }
+    }
+
+    #[test]
+    fn test_explicit_input_format_is_used() {
+        assert_eq!(
+            parsed(parse_file("a: b", Some(&ConfigFileType::Yaml), None)),
+            json!({ "a": "b" })
+        );
+        assert_eq!(
+            parsed(parse_file(
+                "db.host=localhost",
+                Some(&ConfigFileType::Properties),
+                None
+            )),
+            json!({ "db": { "host": "localhost" } })
+        );
+    }
+
+    #[test]
+    fn test_parse_error_names_format_and_line() {
+        let yaml = "a: b\nc:\n  - d\n e: f\n";
+        match parse_file_to_json(yaml, &ConfigFileType::Yaml) {
+            Err(Error::FormatParseError(format, message)) => {
+                assert_eq!(format, "Yaml");
+                assert!(message.contains("line 4"), "{}", message);
+            }
+            _ => panic!("Expected Error::FormatParseError"),
+        }
+
+        let error = error_message(parse_file_to_json("a = 1\nb = \n", &ConfigFileType::Toml));
+        assert!(error.contains("as Toml"), "{}", error);
+        assert!(error.contains("line 2"), "{}", error);
+
+        let error = error_message(parse_file_to_json(
+            "A=1\nnot an env line\n",
+            &ConfigFileType::EnvFile,
+        ));
+        assert!(error.contains("line 2"), "{}", error);
+
+        let error = error_message(parse_file_to_json("[1, 2]", &ConfigFileType::Json));
+        assert!(error.contains("not an object"), "{}", error);
+    }
+
+    #[test]
+    fn test_content_type_hint_is_tried_before_parser_chain() {
+        let hint = to_file_type_from_content_type("application/yaml; charset=utf-8");
+        assert!(matches!(hint, Some(ConfigFileType::Yaml)));
+        assert!(to_file_type_from_content_type("text/plain").is_none());
+
+        assert_eq!(
+            parsed(parse_file("a: b", None, hint.as_ref())),
+            json!({ "a": "b" })
+        );
+        // Wrong hint does not prevent content from being parsed
+        assert!(parse_file("a: b", None, Some(&ConfigFileType::Toml)).is_ok());
+    }
+
+    #[test]
+    fn test_parser_chain_stops_at_format_recognizing_content() {
+        match try_parse_file_to_json("[1, 2]") {
+            Err(Error::FormatParseError(format, message)) => {
+                assert_eq!(format, "Json");
+                assert!(message.contains("not an object"), "{}", message);
+            }
+            _ => panic!("Expected Error::FormatParseError"),
+        }
+
+        let error = error_message(try_parse_file_to_json("- a\n- b\n"));
+        assert!(error.contains("as Yaml"), "{}", error);
+        assert!(error.contains("not an object"), "{}", error);
+
+        // Plain text is not taken for YAML scalar
+        assert_eq!(
+            parsed(try_parse_file_to_json("db.host localhost")),
+            json!({ "db": { "host": "localhost" } })
+        );
+    }
+}
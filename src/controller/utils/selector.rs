@@ -0,0 +1,120 @@
+use crate::contract::lib::Error;
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Selects part of parsed document. Selector is either JSON Pointer (`/database/password`),
+/// or JSONPath subset of child and index accessors (`$.database.password`, `$['log.level']`, `$.hosts[0]`).
+pub fn select<'a>(document: &'a JsonValue, selector: &str) -> Result<&'a JsonValue, Error> {
+    let segments = parse_selector(selector)?;
+    segments
+        .iter()
+        .try_fold(document, |value, segment| match (segment, value) {
+            (Segment::Key(key), JsonValue::Object(map)) => map.get(key),
+            (Segment::Index(index), JsonValue::Array(items)) => items.get(*index),
+            // JSON Pointer does not tell keys and indexes apart
+            (Segment::Key(key), JsonValue::Array(items)) => {
+                key.parse::<usize>().ok().and_then(|index| items.get(index))
+            }
+            _ => None,
+        })
+        .ok_or_else(|| Error::SelectorNoMatch(selector.to_string()))
+}
+
+/// Every scalar under `value`, keyed by its path relative to `value` with segments joined by `.`.
+/// Empty objects and arrays have no leaves and are left out.
+pub fn explode(value: &JsonValue) -> BTreeMap<String, String> {
+    let mut leaves = BTreeMap::new();
+    collect_leaves(value, String::new(), &mut leaves);
+    leaves
+}
+
+/// Scalar as written into data key, strings without JSON quotes. `None` for objects and arrays.
+pub fn scalar_to_string(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::String(value) => Some(value.clone()),
+        JsonValue::Null => Some(String::new()),
+        JsonValue::Bool(_) | JsonValue::Number(_) => Some(value.to_string()),
+        JsonValue::Object(_) | JsonValue::Array(_) => None,
+    }
+}
+
+fn collect_leaves(value: &JsonValue, path: String, leaves: &mut BTreeMap<String, String>) {
+    let child_path = |segment: &str| {
+        if path.is_empty() {
+            segment.to_string()
+        } else {
+            format!("{}.{}", path, segment)
+        }
+    };
+    match value {
+        JsonValue::Object(map) => {
+            for (key, child) in map {
+                collect_leaves(child, child_path(key), leaves);
+            }
+        }
+        JsonValue::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                collect_leaves(child, child_path(&index.to_string()), leaves);
+            }
+        }
+        scalar => {
+            if let Some(value) = scalar_to_string(scalar) {
+                leaves.insert(path, value);
+            }
+        }
+    }
+}
+
+fn parse_selector(selector: &str) -> Result<Vec<Segment>, Error> {
+    if selector.is_empty() || selector.starts_with('/') {
+        Ok(parse_pointer(selector))
+    } else if let Some(path) = selector.strip_prefix('$') {
+        parse_path(path).ok_or_else(|| Error::InvalidSelector(selector.to_string()))
+    } else {
+        Err(Error::InvalidSelector(selector.to_string()))
+    }
+}
+
+fn parse_pointer(pointer: &str) -> Vec<Segment> {
+    pointer
+        .split('/')
+        .skip(1)
+        .map(|token| Segment::Key(token.replace("~1", "/").replace("~0", "~")))
+        .collect()
+}
+
+fn parse_path(mut path: &str) -> Option<Vec<Segment>> {
+    let mut segments = Vec::new();
+    while !path.is_empty() {
+        if let Some(rest) = path.strip_prefix('.') {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            if end == 0 {
+                return None;
+            }
+            segments.push(Segment::Key(rest[..end].to_string()));
+            path = &rest[end..];
+        } else if let Some(rest) = path.strip_prefix('[') {
+            let end = rest.find(']')?;
+            let inner = rest[..end].trim();
+            let quoted = ['\'', '"'].iter().find_map(|quote| {
+                inner
+                    .strip_prefix(*quote)
+                    .and_then(|inner| inner.strip_suffix(*quote))
+            });
+            segments.push(match quoted {
+                Some(key) => Segment::Key(key.to_string()),
+                None => Segment::Index(inner.parse().ok()?),
+            });
+            path = &rest[end + 1..];
+        } else {
+            return None;
+        }
+    }
+    Some(segments)
+}
@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::lib::Error;
+    use crate::controller::utils::selector::{explode, scalar_to_string, select};
+    use serde_json::json;
+
+    fn document() -> serde_json::Value {
+        json!({
+            "database": { "host": "db", "port": 5432, "password": "s3cret" },
+            "log.level": "info",
+            "hosts": ["a", "b"],
+            "a/b": true
+        })
+    }
+
+    #[test]
+    fn test_json_pointer_and_path_select_same_value() {
+        let document = document();
+
+        for selector in [
+            "/database/password",
+            "$.database.password",
+            "$['database']['password']",
+        ] {
+            assert_eq!(
+                select(&document, selector).unwrap(),
+                "s3cret",
+                "{}",
+                selector
+            );
+        }
+        assert_eq!(select(&document, "/hosts/1").unwrap(), "b");
+        assert_eq!(select(&document, "$.hosts[1]").unwrap(), "b");
+        assert_eq!(select(&document, "$[\"log.level\"]").unwrap(), "info");
+        assert_eq!(select(&document, "/a~1b").unwrap(), true);
+        assert_eq!(select(&document, "$").unwrap(), &document);
+    }
+
+    #[test]
+    fn test_invalid_or_missing_selector_is_error() {
+        let document = document();
+
+        assert!(matches!(
+            select(&document, "database.password"),
+            Err(Error::InvalidSelector(_))
+        ));
+        assert!(matches!(
+            select(&document, "$.hosts[x]"),
+            Err(Error::InvalidSelector(_))
+        ));
+        assert!(matches!(
+            select(&document, "/database/user"),
+            Err(Error::SelectorNoMatch(_))
+        ));
+        assert!(matches!(
+            select(&document, "$.hosts[5]"),
+            Err(Error::SelectorNoMatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_explode_keys_leaves_by_relative_path() {
+        let document = document();
+
+        let leaves = explode(select(&document, "/database").unwrap());
+        assert_eq!(
+            leaves.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("host".to_string(), "db".to_string()),
+                ("password".to_string(), "s3cret".to_string()),
+                ("port".to_string(), "5432".to_string()),
+            ]
+        );
+
+        let leaves = explode(&document);
+        assert_eq!(leaves["hosts.0"], "a");
+        assert_eq!(leaves["database.port"], "5432");
+        assert_eq!(leaves["a/b"], "true");
+    }
+
+    #[test]
+    fn test_scalars_are_written_without_quotes() {
+        assert_eq!(scalar_to_string(&json!("text")).unwrap(), "text");
+        assert_eq!(scalar_to_string(&json!(1.5)).unwrap(), "1.5");
+        assert_eq!(scalar_to_string(&json!(null)).unwrap(), "");
+        assert!(scalar_to_string(&json!({ "a": 1 })).is_none());
+    }
+}
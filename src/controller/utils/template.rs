@@ -0,0 +1,86 @@
+use crate::contract::lib::Error;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use tera::{Context, Tera};
+
+/// Renders Tera template with composed document as context. Keys of the document are top
+/// level variables, document which is not an object is available as `value`. Undefined
+/// variables fail rendering, unless handled with `default` filter.
+///
+/// Besides Tera builtins (`upper`, `lower`, `default`, ...) Helm style filters are available:
+/// `b64enc`, `toJson`, `toYaml` and `required`, which fails on null or empty value.
+/// Builtin `get_env` is disabled, templates must not read environment of the operator.
+pub fn render_template(source: &str, document: &JsonValue) -> Result<String, Error> {
+    let mut tera = Tera::default();
+    tera.register_function("get_env", get_env_disabled);
+    tera.register_filter("b64enc", b64enc);
+    tera.register_filter("toJson", to_json);
+    tera.register_filter("toYaml", to_yaml);
+    tera.register_filter("required", required);
+
+    let context = match document {
+        JsonValue::Object(_) => Context::from_value(document.clone()),
+        value => {
+            let mut context = Context::new();
+            context.insert("value", value);
+            Ok(context)
+        }
+    }
+    .map_err(|e| Error::TemplateError(error_chain(&e)))?;
+
+    tera.render_str(source, &context)
+        .map_err(|e| Error::TemplateError(error_chain(&e)))
+}
+
+/// Tera reports cause of failure, e.g. undefined variable, as source of generic error.
+fn error_chain(error: &tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message = format!("{}: {}", message, cause);
+        source = cause.source();
+    }
+    message
+}
+
+fn get_env_disabled(_: &HashMap<String, JsonValue>) -> tera::Result<JsonValue> {
+    Err(tera::Error::msg("get_env is not available in templates"))
+}
+
+fn b64enc(value: &JsonValue, _: &HashMap<String, JsonValue>) -> tera::Result<JsonValue> {
+    let text = match value {
+        JsonValue::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+    Ok(JsonValue::String(STANDARD.encode(text)))
+}
+
+fn to_json(value: &JsonValue, _: &HashMap<String, JsonValue>) -> tera::Result<JsonValue> {
+    serde_json::to_string(value)
+        .map(JsonValue::String)
+        .map_err(|e| tera::Error::msg(e.to_string()))
+}
+
+fn to_yaml(value: &JsonValue, _: &HashMap<String, JsonValue>) -> tera::Result<JsonValue> {
+    serde_yaml::to_string(value)
+        .map(|yaml| JsonValue::String(yaml.trim_end().to_string()))
+        .map_err(|e| tera::Error::msg(e.to_string()))
+}
+
+fn required(value: &JsonValue, args: &HashMap<String, JsonValue>) -> tera::Result<JsonValue> {
+    let empty = match value {
+        JsonValue::Null => true,
+        JsonValue::String(text) => text.is_empty(),
+        _ => false,
+    };
+    if empty {
+        let message = args
+            .get("message")
+            .and_then(JsonValue::as_str)
+            .unwrap_or("required value is empty");
+        return Err(tera::Error::msg(message));
+    }
+    Ok(value.clone())
+}
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::lib::Error;
+    use crate::controller::utils::template::render_template;
+    use serde_json::json;
+
+    fn document() -> serde_json::Value {
+        json!({
+            "database": { "host": "db", "port": 5432, "name": "app", "password": "s3cret" },
+            "upstreams": ["a:80", "b:80"],
+            "empty": ""
+        })
+    }
+
+    #[test]
+    fn test_renders_text_file_from_document() {
+        let template = "url=jdbc:postgresql://{{ database.host }}:{{ database.port }}/{{ database.name | upper }}\n\
+            {% for upstream in upstreams %}server {{ upstream }};\n{% endfor %}";
+
+        let rendered = render_template(template, &document()).unwrap();
+
+        assert_eq!(
+            rendered,
+            "url=jdbc:postgresql://db:5432/APP\nserver a:80;\nserver b:80;\n"
+        );
+    }
+
+    #[test]
+    fn test_helper_filters() {
+        let document = document();
+        let render = |template: &str| render_template(template, &document).unwrap();
+
+        assert_eq!(render("{{ database.password | b64enc }}"), "czNjcmV0");
+        assert_eq!(render("{{ upstreams | toJson }}"), "[\"a:80\",\"b:80\"]");
+        assert_eq!(render("{{ upstreams | toYaml }}"), "- a:80\n- b:80");
+        assert_eq!(render("{{ missing | default(value=\"X\") | lower }}"), "x");
+    }
+
+    #[test]
+    fn test_missing_or_empty_required_value_fails_rendering() {
+        let document = document();
+
+        let error = render_template("{{ database.user }}", &document).unwrap_err();
+        assert!(matches!(error, Error::TemplateError(_)));
+        assert!(error.to_string().contains("database.user"), "{}", error);
+
+        let error = render_template(
+            "{{ empty | required(message=\"empty must be set\") }}",
+            &document,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("empty must be set"), "{}", error);
+    }
+
+    #[test]
+    fn test_scalar_document_is_available_as_value() {
+        assert_eq!(
+            render_template("password={{ value }}", &json!("s3cret")).unwrap(),
+            "password=s3cret"
+        );
+    }
+
+    #[test]
+    fn test_operator_environment_is_not_readable() {
+        std::env::set_var("TEMPLATE_TEST_SECRET", "s3cret");
+
+        let result = render_template("{{ get_env(name=\"TEMPLATE_TEST_SECRET\") }}", &document());
+
+        assert!(matches!(result, Err(Error::TemplateError(_))));
+        let result = render_template(
+            "{{ get_env(name=\"TEMPLATE_TEST_SECRET\", default=\"x\") }}",
+            &document(),
+        );
+        assert!(matches!(result, Err(Error::TemplateError(_))));
+    }
+}
@@ -0,0 +1,189 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header carrying `sha256=<hex>` HMAC-SHA256 of signed request, keyed by webhook secret.
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// Header carrying time request was signed at, in seconds since Unix epoch.
+pub const TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+
+/// How far timestamp of request may be from the time it is received, so captured requests
+/// cannot be replayed later.
+pub const MAX_TIMESTAMP_SKEW: Duration = Duration::from_secs(300);
+
+/// Why store change notification was not accepted.
+#[derive(Debug, PartialEq)]
+pub enum WebhookRejection {
+    /// No secret configured, unauthenticated notifications are never accepted
+    Disabled,
+    InvalidSignature,
+    /// Timestamp is missing or too far from current time
+    StaleTimestamp,
+    /// Store was notified recently, carries time left until next notification is accepted
+    RateLimited(Duration),
+}
+
+/// Authenticates and rate limits store change notifications.
+pub struct StoreWebhook {
+    secret: Option<Vec<u8>>,
+    rate_limiter: RateLimiter,
+}
+
+impl StoreWebhook {
+    pub fn new(secret: Option<String>, min_interval: Duration) -> Self {
+        Self {
+            secret: secret.map(String::into_bytes),
+            rate_limiter: RateLimiter::new(min_interval),
+        }
+    }
+
+    /// Checks signature and its timestamp. Done before rate limit, so unauthenticated callers
+    /// cannot exhaust it.
+    pub fn authenticate(
+        &self,
+        request: &SignedRequest,
+        received_at: SystemTime,
+    ) -> Result<(), WebhookRejection> {
+        let secret = self.secret.as_ref().ok_or(WebhookRejection::Disabled)?;
+        let Some(timestamp) = request.timestamp else {
+            return Err(WebhookRejection::StaleTimestamp);
+        };
+        if !verify_signature(secret, request) {
+            return Err(WebhookRejection::InvalidSignature);
+        }
+        if !timestamp_fresh(timestamp, received_at) {
+            return Err(WebhookRejection::StaleTimestamp);
+        }
+        Ok(())
+    }
+
+    /// Passes authenticated notification of store on with `send`, unless store was notified
+    /// recently. Returns whether it was sent, only sent notification starts the interval.
+    pub fn notify(
+        &self,
+        store: &str,
+        now: Instant,
+        send: impl FnOnce() -> bool,
+    ) -> Result<bool, WebhookRejection> {
+        self.rate_limiter
+            .try_acquire(store, now, send)
+            .map_err(WebhookRejection::RateLimited)
+    }
+}
+
+/// Accepts one notification per store within interval. Repeated notifications would only
+/// fetch the same data again, so they are rejected instead of queued.
+pub struct RateLimiter {
+    interval: Duration,
+    last_accepted: Mutex<HashMap<String, Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_accepted: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Runs `send` unless notification for key was accepted within interval, in which case
+    /// time left until next one is accepted is returned. Notification counts as accepted only
+    /// when `send` reports it was delivered.
+    pub fn try_acquire(
+        &self,
+        key: &str,
+        now: Instant,
+        send: impl FnOnce() -> bool,
+    ) -> Result<bool, Duration> {
+        let mut last_accepted = self.last_accepted.lock().unwrap();
+        if let Some(previous) = last_accepted.get(key) {
+            let elapsed = now.saturating_duration_since(*previous);
+            if elapsed < self.interval {
+                return Err(self.interval - elapsed);
+            }
+        }
+        let sent = send();
+        if sent {
+            last_accepted.insert(key.to_string(), now);
+        }
+        Ok(sent)
+    }
+}
+
+/// Store change notification as received, with parts covered by its signature.
+pub struct SignedRequest<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    pub timestamp: Option<&'a str>,
+    pub body: &'a [u8],
+    pub signature: Option<&'a str>,
+}
+
+/// Signature of request, `sha256=<hex>` HMAC-SHA256 of timestamp, method, path and body,
+/// each but the body followed by newline.
+pub fn sign(secret: &[u8], method: &str, path: &str, timestamp: &str, body: &[u8]) -> String {
+    let mac = signed_message_mac(secret, method, path, timestamp, body);
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
+/// Verifies `sha256=<hex>` signature of request, comparing in constant time.
+pub fn verify_signature(secret: &[u8], request: &SignedRequest) -> bool {
+    let (Some(timestamp), Some(signature)) = (request.timestamp, request.signature) else {
+        return false;
+    };
+    let Some(actual) = signature.strip_prefix("sha256=").and_then(decode_hex) else {
+        return false;
+    };
+    signed_message_mac(
+        secret,
+        request.method,
+        request.path,
+        timestamp,
+        request.body,
+    )
+    .verify_slice(&actual)
+    .is_ok()
+}
+
+fn signed_message_mac(
+    secret: &[u8],
+    method: &str,
+    path: &str,
+    timestamp: &str,
+    body: &[u8],
+) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts key of any length");
+    for part in [timestamp, method, path] {
+        mac.update(part.as_bytes());
+        mac.update(b"\n");
+    }
+    mac.update(body);
+    mac
+}
+
+/// Whether timestamp is within `MAX_TIMESTAMP_SKEW` of `received_at`, in either direction.
+pub fn timestamp_fresh(timestamp: &str, received_at: SystemTime) -> bool {
+    let Ok(signed_at) = timestamp.parse::<u64>() else {
+        return false;
+    };
+    let received_at = received_at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    received_at.abs_diff(signed_at) <= MAX_TIMESTAMP_SKEW.as_secs()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
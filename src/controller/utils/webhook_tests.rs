@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use crate::controller::utils::webhook::{
+        sign, timestamp_fresh, verify_signature, SignedRequest, StoreWebhook, WebhookRejection,
+    };
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+    const PATH: &str = "/webhook/stores/default/store";
+    const TIMESTAMP: &str = "1700000000";
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    fn request<'a>(
+        method: &'a str,
+        path: &'a str,
+        body: &'a [u8],
+        signature: Option<&'a str>,
+    ) -> SignedRequest<'a> {
+        SignedRequest {
+            method,
+            path,
+            timestamp: Some(TIMESTAMP),
+            body,
+            signature,
+        }
+    }
+
+    #[test]
+    fn test_signature_covers_timestamp_method_path_and_body() {
+        let signed = sign(b"secret", "POST", PATH, TIMESTAMP, b"{}");
+        assert_eq!(
+            signed,
+            "sha256=42733065092d4a5db96bd59d5a7d5099ad0f7b5a03c7396341a913995c321bce"
+        );
+
+        let signed_request = request("POST", PATH, b"{}", Some(&signed));
+        assert!(verify_signature(b"secret", &signed_request));
+        assert!(!verify_signature(b"other", &signed_request));
+        let modified = [
+            request("POST", PATH, b"{ }", Some(&signed)),
+            request("PUT", PATH, b"{}", Some(&signed)),
+            request(
+                "POST",
+                "/webhook/stores/default/other",
+                b"{}",
+                Some(&signed),
+            ),
+            SignedRequest {
+                timestamp: Some("1700000001"),
+                ..request("POST", PATH, b"{}", Some(&signed))
+            },
+            request("POST", PATH, b"{}", Some(&signed[7..])),
+            request("POST", PATH, b"{}", Some("sha256=zz")),
+            request("POST", PATH, b"{}", None),
+        ];
+        for request in &modified {
+            assert!(!verify_signature(b"secret", request));
+        }
+    }
+
+    #[test]
+    fn test_timestamp_must_be_recent() {
+        assert!(timestamp_fresh(TIMESTAMP, at(1_700_000_000)));
+        assert!(timestamp_fresh(TIMESTAMP, at(1_700_000_300)));
+        assert!(timestamp_fresh(TIMESTAMP, at(1_699_999_700)));
+        assert!(!timestamp_fresh(TIMESTAMP, at(1_700_000_301)));
+        assert!(!timestamp_fresh(TIMESTAMP, at(1_699_999_699)));
+        assert!(!timestamp_fresh("yesterday", at(1_700_000_000)));
+    }
+
+    #[test]
+    fn test_notifications_are_authenticated() {
+        let webhook = StoreWebhook::new(Some("secret".to_string()), Duration::from_secs(10));
+        let received_at = at(1_700_000_000);
+        let signed = sign(b"secret", "POST", PATH, TIMESTAMP, b"");
+
+        assert_eq!(
+            webhook.authenticate(&request("POST", PATH, b"", None), received_at),
+            Err(WebhookRejection::InvalidSignature)
+        );
+        assert_eq!(
+            webhook.authenticate(&request("POST", PATH, b"", Some(&signed)), received_at),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_only_sent_notifications_are_rate_limited() {
+        let webhook = StoreWebhook::new(Some("secret".to_string()), Duration::from_secs(10));
+        let now = Instant::now();
+        let store = "ConfigurationStore/default/store";
+
+        // Notification not sent, e.g. when controllers are not running, does not count
+        assert_eq!(webhook.notify(store, now, || false), Ok(false));
+        assert_eq!(webhook.notify(store, now, || true), Ok(true));
+        assert_eq!(
+            webhook.notify(store, now + Duration::from_secs(4), || {
+                panic!("Rate limited notification should not be sent")
+            }),
+            Err(WebhookRejection::RateLimited(Duration::from_secs(6)))
+        );
+        assert_eq!(
+            webhook.notify("ConfigurationStore/default/other", now, || true),
+            Ok(true)
+        );
+        assert_eq!(
+            webhook.notify(store, now + Duration::from_secs(10), || true),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_replayed_notification_is_rejected() {
+        let webhook = StoreWebhook::new(Some("secret".to_string()), Duration::from_secs(10));
+        let signed = sign(b"secret", "POST", PATH, TIMESTAMP, b"");
+
+        assert_eq!(
+            webhook.authenticate(
+                &request("POST", PATH, b"", Some(&signed)),
+                at(1_700_003_600)
+            ),
+            Err(WebhookRejection::StaleTimestamp)
+        );
+        let without_timestamp = SignedRequest {
+            timestamp: None,
+            ..request("POST", PATH, b"", Some(&signed))
+        };
+        assert_eq!(
+            webhook.authenticate(&without_timestamp, at(1_700_000_000)),
+            Err(WebhookRejection::StaleTimestamp)
+        );
+    }
+
+    #[test]
+    fn test_webhook_without_secret_is_disabled() {
+        let webhook = StoreWebhook::new(None, Duration::from_secs(10));
+
+        assert_eq!(
+            webhook.authenticate(
+                &request("POST", PATH, b"", Some("sha256=00")),
+                SystemTime::now()
+            ),
+            Err(WebhookRejection::Disabled)
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use crate::contract::lib::Error;
+use crate::controller::utils::conditions::{
+    condition, set_condition, CONDITION_READY, CONDITION_SYNCED,
+};
+use crate::controller::v1alpha1::crd::claim::{ClaimStatus, DataKeyStatus};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// Target written by successful sync.
+pub struct SyncedTarget {
+    pub name: String,
+    pub resource_version: Option<String>,
+    /// Content hash rollout targets were restarted for, `None` without rollout targets
+    pub rollout_hash: Option<String>,
+}
+
+/// `sha256:` digest of composed file, lets users tell whether content changed between syncs.
+pub fn content_hash(content: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(content))
+}
+
+/// Status of single data key after compose attempt. On failure, results of last successful
+/// sync are kept next to the error, as target still holds that content. Failed attempts do not
+/// touch timestamps, so repeated failure does not produce new status update.
+pub fn data_key_status(
+    previous: Option<&ClaimStatus>,
+    key: &str,
+    result: Result<(&[String], &[u8]), &Error>,
+    now: DateTime<Utc>,
+) -> DataKeyStatus {
+    let mut status = previous
+        .and_then(|status| status.data.as_ref())
+        .and_then(|data| data.iter().find(|entry| entry.key == key))
+        .cloned()
+        .unwrap_or_else(|| DataKeyStatus {
+            key: key.to_string(),
+            ..Default::default()
+        });
+
+    match result {
+        Ok((stores, content)) => {
+            status.stores = Some(stores.to_vec());
+            status.hash = Some(content_hash(content));
+            status.last_synced = Some(now.to_rfc3339());
+            status.last_error = None;
+        }
+        Err(e) => status.last_error = Some(e.to_string()),
+    }
+    status
+}
+
+/// Status reflecting reconcile result. `Synced` tells whether the last attempt succeeded,
+/// `Ready` whether target holds data for current generation of the claim, which stays true
+/// when only a refresh failed.
+pub fn claim_status(
+    previous: Option<&ClaimStatus>,
+    generation: Option<i64>,
+    data: Vec<DataKeyStatus>,
+    result: &Result<SyncedTarget, Error>,
+    now: DateTime<Utc>,
+) -> ClaimStatus {
+    let mut status = previous.cloned().unwrap_or_default();
+    let mut conditions = status.conditions.take().unwrap_or_default();
+    let ready_for_generation = status.observed_generation == generation
+        && conditions
+            .iter()
+            .any(|c| c.type_ == CONDITION_READY && c.status == "True");
+
+    status.data = Some(data);
+    status.observed_generation = generation;
+
+    match result {
+        Ok(target) => {
+            status.last_synced = Some(now.to_rfc3339());
+            status.target_name = Some(target.name.clone());
+            status.target_resource_version = target.resource_version.clone();
+            status.rollout_hash = target.rollout_hash.clone();
+            set_condition(
+                &mut conditions,
+                condition(CONDITION_SYNCED, true, "Synced", "", generation),
+            );
+            set_condition(
+                &mut conditions,
+                condition(CONDITION_READY, true, "TargetSynced", "", generation),
+            );
+        }
+        Err(e) => {
+            let message = e.to_string();
+            let reason = failure_reason(e);
+            set_condition(
+                &mut conditions,
+                condition(CONDITION_SYNCED, false, reason, &message, generation),
+            );
+            if !ready_for_generation {
+                set_condition(
+                    &mut conditions,
+                    condition(CONDITION_READY, false, reason, &message, generation),
+                );
+            }
+        }
+    }
+
+    status.conditions = Some(conditions);
+    status
+}
+
+fn failure_reason(error: &Error) -> &'static str {
+    match error {
+        Error::TargetApplyConflict(_) => "ApplyConflict",
+        Error::TargetNotFound(_) => "TargetNotFound",
+        _ => "SyncFailed",
+    }
+}
+
+/// Target written by last sync, when it succeeded for current generation of the claim.
+pub fn unchanged_target(
+    status: Option<&ClaimStatus>,
+    generation: Option<i64>,
+) -> Option<SyncedTarget> {
+    let status = status?;
+    let synced = status
+        .conditions
+        .as_ref()?
+        .iter()
+        .any(|c| c.type_ == CONDITION_SYNCED && c.status == "True");
+    if !synced || status.observed_generation != generation {
+        return None;
+    }
+    Some(SyncedTarget {
+        name: status.target_name.clone()?,
+        resource_version: status.target_resource_version.clone(),
+        rollout_hash: status.rollout_hash.clone(),
+    })
+}
+
+/// Time left until next refresh is due, or `None` when claim should be synced now.
+/// Writing status triggers another reconcile, so claim synced for current generation
+/// is not synced again before its refresh interval passes.
+pub fn next_sync_in(
+    status: Option<&ClaimStatus>,
+    generation: Option<i64>,
+    interval: Duration,
+    now: DateTime<Utc>,
+) -> Option<Duration> {
+    let status = status?;
+    let synced = status
+        .conditions
+        .as_ref()?
+        .iter()
+        .any(|c| c.type_ == CONDITION_SYNCED && c.status == "True");
+    if !synced || status.observed_generation != generation {
+        return None;
+    }
+    let last_synced = DateTime::parse_from_rfc3339(status.last_synced.as_deref()?).ok()?;
+    let elapsed = (now - last_synced.with_timezone(&Utc))
+        .to_std()
+        .unwrap_or_default();
+    interval
+        .checked_sub(elapsed)
+        .filter(|remaining| !remaining.is_zero())
+}
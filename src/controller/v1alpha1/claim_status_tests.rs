@@ -0,0 +1,180 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::lib::Error;
+    use crate::controller::v1alpha1::claim_status::{
+        claim_status, content_hash, data_key_status, next_sync_in, unchanged_target, SyncedTarget,
+    };
+    use crate::controller::v1alpha1::crd::claim::ClaimStatus;
+    use chrono::{TimeDelta, Utc};
+    use std::time::Duration;
+
+    fn condition_status(status: &ClaimStatus, type_: &str) -> (String, String) {
+        let condition = status
+            .conditions
+            .as_ref()
+            .and_then(|conditions| conditions.iter().find(|c| c.type_ == type_))
+            .expect("Condition should be set");
+        (condition.status.clone(), condition.reason.clone())
+    }
+
+    fn store_error() -> Error {
+        Error::HttpConfigStoreServerError(std::io::Error::other("unavailable"))
+    }
+
+    fn synced(generation: i64) -> ClaimStatus {
+        let stores = vec![String::from("ConfigurationStore/http")];
+        let key = data_key_status(None, "app.json", Ok((&stores, "{}".as_bytes())), Utc::now());
+        claim_status(
+            None,
+            Some(generation),
+            vec![key],
+            &Ok(SyncedTarget {
+                name: String::from("app-config"),
+                resource_version: Some(String::from("42")),
+                rollout_hash: None,
+            }),
+            Utc::now(),
+        )
+    }
+
+    #[test]
+    fn test_successful_sync_marks_claim_ready_and_synced() {
+        let status = synced(1);
+
+        assert_eq!(
+            condition_status(&status, "Ready"),
+            ("True".into(), "TargetSynced".into())
+        );
+        assert_eq!(condition_status(&status, "Synced").0, "True");
+        assert_eq!(status.observed_generation, Some(1));
+        assert_eq!(status.target_name.as_deref(), Some("app-config"));
+        assert_eq!(status.target_resource_version.as_deref(), Some("42"));
+
+        let key = &status.data.unwrap()[0];
+        assert_eq!(key.key, "app.json");
+        assert_eq!(key.hash, Some(content_hash(b"{}")));
+        assert_eq!(
+            key.stores,
+            Some(vec![String::from("ConfigurationStore/http")])
+        );
+        assert!(key.last_synced.is_some());
+    }
+
+    #[test]
+    fn test_failed_refresh_keeps_claim_ready_and_last_synced_content() {
+        let previous = synced(1);
+        let error = store_error();
+        let key = data_key_status(Some(&previous), "app.json", Err(&error), Utc::now());
+
+        let status = claim_status(
+            Some(&previous),
+            Some(1),
+            vec![key],
+            &Err(store_error()),
+            Utc::now(),
+        );
+
+        assert_eq!(condition_status(&status, "Ready").0, "True");
+        assert_eq!(
+            condition_status(&status, "Synced"),
+            ("False".into(), "SyncFailed".into())
+        );
+        assert_eq!(status.last_synced, previous.last_synced);
+        let key = &status.data.unwrap()[0];
+        assert_eq!(key.hash, Some(content_hash(b"{}")));
+        assert!(key.last_error.as_ref().unwrap().contains("unavailable"));
+    }
+
+    #[test]
+    fn test_failed_sync_of_new_generation_marks_claim_not_ready() {
+        let previous = synced(1);
+
+        let status = claim_status(
+            Some(&previous),
+            Some(2),
+            vec![],
+            &Err(store_error()),
+            Utc::now(),
+        );
+
+        assert_eq!(
+            condition_status(&status, "Ready"),
+            ("False".into(), "SyncFailed".into())
+        );
+        assert_eq!(status.observed_generation, Some(2));
+    }
+
+    #[test]
+    fn test_repeated_failure_produces_same_status() {
+        let previous = synced(1);
+        let error = store_error();
+        let failed = |previous: &ClaimStatus| {
+            let key = data_key_status(Some(previous), "app.json", Err(&error), Utc::now());
+            claim_status(
+                Some(previous),
+                Some(1),
+                vec![key],
+                &Err(store_error()),
+                Utc::now(),
+            )
+        };
+
+        let first = failed(&previous);
+        assert_eq!(failed(&first), first);
+    }
+
+    #[test]
+    fn test_synced_claim_is_not_synced_again_before_refresh_interval() {
+        let now = Utc::now();
+        let interval = Duration::from_secs(60);
+        let status = synced(1);
+
+        assert!(next_sync_in(Some(&status), Some(1), interval, now).is_some());
+        assert!(next_sync_in(Some(&status), Some(2), interval, now).is_none());
+        assert!(next_sync_in(
+            Some(&status),
+            Some(1),
+            interval,
+            now + TimeDelta::seconds(61)
+        )
+        .is_none());
+
+        let failed = claim_status(Some(&status), Some(1), vec![], &Err(store_error()), now);
+        assert!(next_sync_in(Some(&failed), Some(1), interval, now).is_none());
+    }
+
+    #[test]
+    fn test_unchanged_content_keeps_target_of_successful_sync() {
+        let status = synced(1);
+
+        let target = unchanged_target(Some(&status), Some(1)).expect("Target should be kept");
+        assert_eq!(target.name, "app-config");
+        assert_eq!(target.resource_version.as_deref(), Some("42"));
+        assert!(unchanged_target(Some(&status), Some(2)).is_none());
+        assert!(unchanged_target(None, Some(1)).is_none());
+
+        // Failed apply must be retried even when stores serve the same content
+        let failed = claim_status(
+            Some(&status),
+            Some(1),
+            vec![],
+            &Err(store_error()),
+            Utc::now(),
+        );
+        assert!(unchanged_target(Some(&failed), Some(1)).is_none());
+    }
+
+    #[test]
+    fn test_apply_conflict_is_reported_as_condition_reason() {
+        let conflict = Error::TargetApplyConflict(String::from(
+            "Apply failed with 1 conflict: conflict with \"helm\": .data.app.json",
+        ));
+
+        let status = claim_status(None, Some(1), vec![], &Err(conflict), Utc::now());
+
+        let (state, reason) = condition_status(&status, "Synced");
+        assert_eq!(state, "False");
+        assert_eq!(reason, "ApplyConflict");
+        assert_eq!(condition_status(&status, "Ready").1, "ApplyConflict");
+    }
+}
@@ -0,0 +1,421 @@
+use crate::controller::v1alpha1::crd::claim::{
+    HasClaimData, HasTarget, SupportedConfigurationStoreResourceType,
+};
+use futures::{stream, Stream, StreamExt};
+use kube::runtime::reflector::{ObjectRef, Store};
+use kube::runtime::watcher::{self, Event};
+use kube::{Resource, ResourceExt};
+use log::warn;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+const STORE_CHANGES_CAPACITY: usize = 64;
+
+/// Claims whose next reconcile syncs regardless of refresh interval, because target or store
+/// they depend on changed. Watches mark claims, reconcile takes the mark.
+#[derive(Default)]
+pub struct ResyncRequests {
+    claims: Mutex<HashSet<String>>,
+    /// Resource version of target written by last sync of claim. Watch event of the write
+    /// may arrive before claim status records it, so it is known here first.
+    written: Mutex<HashMap<String, String>>,
+}
+
+impl ResyncRequests {
+    pub fn request<K: Resource<DynamicType = ()>>(&self, claim: &ObjectRef<K>) {
+        self.claims.lock().unwrap().insert(claim.to_string());
+    }
+
+    /// Returns whether resync of the claim was requested, clearing the request.
+    pub fn take<K: Resource<DynamicType = ()>>(&self, claim: &K) -> bool {
+        self.claims
+            .lock()
+            .unwrap()
+            .remove(&ObjectRef::from_obj(claim).to_string())
+    }
+
+    /// Records resource version of target the claim has just written.
+    pub fn record_written<K: Resource<DynamicType = ()>>(
+        &self,
+        claim: &K,
+        resource_version: Option<&str>,
+    ) {
+        let key = ObjectRef::from_obj(claim).to_string();
+        let mut written = self.written.lock().unwrap();
+        match resource_version {
+            Some(resource_version) => written.insert(key, resource_version.to_string()),
+            None => written.remove(&key),
+        };
+    }
+
+    /// Whether target of given resource version was written by the claim itself.
+    pub fn written_by<K: Resource<DynamicType = ()>>(
+        &self,
+        claim: &K,
+        resource_version: Option<&String>,
+    ) -> bool {
+        let written = self.written.lock().unwrap();
+        resource_version.is_some()
+            && written.get(&ObjectRef::from_obj(claim).to_string()) == resource_version
+    }
+}
+
+/// Generations of stores seen by a watch. Store status is written on every probe, so only
+/// change of generation, i.e. of spec, counts, besides store created or deleted. Initial list
+/// on startup only records stores, relist after the watch restarted reports stores which
+/// appeared, changed or disappeared meanwhile.
+pub struct StoreGenerations<S: Resource<DynamicType = ()>> {
+    state: Mutex<StoreGenerationsState<S>>,
+}
+
+struct StoreGenerationsState<S: Resource<DynamicType = ()>> {
+    generations: HashMap<ObjectRef<S>, Option<i64>>,
+    /// Stores listed so far while watch (re)lists
+    relisted: Option<HashSet<ObjectRef<S>>>,
+    initialized: bool,
+}
+
+impl<S: Resource<DynamicType = ()>> Default for StoreGenerations<S> {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(StoreGenerationsState {
+                generations: HashMap::new(),
+                relisted: None,
+                initialized: false,
+            }),
+        }
+    }
+}
+
+impl<S: Resource<DynamicType = ()>> StoreGenerations<S> {
+    /// Stores changed by watch event.
+    pub fn changed(&self, event: &Event<S>) -> Vec<ObjectRef<S>> {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            Event::Init => {
+                state.relisted = Some(HashSet::new());
+                Vec::new()
+            }
+            Event::InitApply(store) => {
+                let store_ref = ObjectRef::from_obj(store);
+                if let Some(relisted) = state.relisted.as_mut() {
+                    relisted.insert(store_ref.clone());
+                }
+                let changed = state.record(store_ref.clone(), store.meta().generation);
+                if changed && state.initialized {
+                    vec![store_ref]
+                } else {
+                    Vec::new()
+                }
+            }
+            Event::InitDone => {
+                let relisted = state.relisted.take().unwrap_or_default();
+                let deleted: Vec<_> = state
+                    .generations
+                    .keys()
+                    .filter(|store_ref| !relisted.contains(*store_ref))
+                    .cloned()
+                    .collect();
+                deleted.iter().for_each(|store_ref| {
+                    state.generations.remove(store_ref);
+                });
+                state.initialized = true;
+                deleted
+            }
+            Event::Apply(store) => {
+                let store_ref = ObjectRef::from_obj(store);
+                if state.record(store_ref.clone(), store.meta().generation) {
+                    vec![store_ref]
+                } else {
+                    Vec::new()
+                }
+            }
+            Event::Delete(store) => {
+                let store_ref = ObjectRef::from_obj(store);
+                state.generations.remove(&store_ref);
+                vec![store_ref]
+            }
+        }
+    }
+}
+
+impl<S: Resource<DynamicType = ()>> StoreGenerationsState<S> {
+    /// Returns whether store is new or its generation differs from the recorded one.
+    fn record(&mut self, store_ref: ObjectRef<S>, generation: Option<i64>) -> bool {
+        self.generations.insert(store_ref, generation) != Some(generation)
+    }
+}
+
+/// Store of given kind and name referenced by claim. Namespace is `None` for
+/// ClusterConfigurationStore, as it is visible to claims of every namespace.
+type StoreKey = (String, Option<String>, String);
+
+/// Claims by stores they fetch data from, kept up to date from claim watch events, so store
+/// changes are mapped to claims without going through all of them.
+pub struct StoreClaims<T: Resource<DynamicType = ()>> {
+    state: Mutex<StoreClaimsState<T>>,
+}
+
+struct StoreClaimsState<T: Resource<DynamicType = ()>> {
+    claims: HashMap<StoreKey, HashSet<ObjectRef<T>>>,
+    stores: HashMap<ObjectRef<T>, HashSet<StoreKey>>,
+    /// Claims listed so far while watch (re)lists, replacing the index once list is done
+    relisted: Option<HashMap<ObjectRef<T>, HashSet<StoreKey>>>,
+}
+
+impl<T: Resource<DynamicType = ()>> Default for StoreClaims<T> {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(StoreClaimsState {
+                claims: HashMap::new(),
+                stores: HashMap::new(),
+                relisted: None,
+            }),
+        }
+    }
+}
+
+impl<T: Resource<DynamicType = ()> + HasClaimData> StoreClaims<T> {
+    pub fn apply_watcher_event(&self, event: &Event<T>) {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            Event::Init => state.relisted = Some(HashMap::new()),
+            Event::InitApply(claim) => {
+                if let Some(relisted) = state.relisted.as_mut() {
+                    relisted.insert(ObjectRef::from_obj(claim), stores_of(claim));
+                }
+            }
+            Event::InitDone => {
+                let relisted = state.relisted.take().unwrap_or_default();
+                state.claims.clear();
+                state.stores.clear();
+                for (claim, stores) in relisted {
+                    state.insert(claim, stores);
+                }
+            }
+            Event::Apply(claim) => {
+                let claim_ref = ObjectRef::from_obj(claim);
+                state.remove(&claim_ref);
+                state.insert(claim_ref, stores_of(claim));
+            }
+            Event::Delete(claim) => state.remove(&ObjectRef::from_obj(claim)),
+        }
+    }
+
+    /// Claims referencing store of given kind and name. ConfigurationStore is only visible
+    /// to claims of its namespace.
+    pub fn claims_referencing(
+        &self,
+        kind: &str,
+        namespace: Option<&str>,
+        name: &str,
+    ) -> Vec<ObjectRef<T>> {
+        let key = (
+            kind.to_string(),
+            namespace.map(String::from),
+            name.to_string(),
+        );
+        self.state
+            .lock()
+            .unwrap()
+            .claims
+            .get(&key)
+            .map(|claims| claims.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Claims fetching data from given ConfigurationStore or ClusterConfigurationStore.
+    pub fn claims_using<S: Resource<DynamicType = ()>>(
+        &self,
+        store: &ObjectRef<S>,
+    ) -> Vec<ObjectRef<T>> {
+        self.claims_referencing(&S::kind(&()), store.namespace.as_deref(), &store.name)
+    }
+}
+
+impl<T: Resource<DynamicType = ()>> StoreClaimsState<T> {
+    fn insert(&mut self, claim: ObjectRef<T>, stores: HashSet<StoreKey>) {
+        for store in &stores {
+            self.claims
+                .entry(store.clone())
+                .or_default()
+                .insert(claim.clone());
+        }
+        self.stores.insert(claim, stores);
+    }
+
+    fn remove(&mut self, claim: &ObjectRef<T>) {
+        for store in self.stores.remove(claim).unwrap_or_default() {
+            if let Some(claims) = self.claims.get_mut(&store) {
+                claims.remove(claim);
+                if claims.is_empty() {
+                    self.claims.remove(&store);
+                }
+            }
+        }
+    }
+}
+
+fn stores_of<T: Resource<DynamicType = ()> + HasClaimData>(claim: &T) -> HashSet<StoreKey> {
+    claim
+        .get_claim_data()
+        .values()
+        .flat_map(|claim_ref| &claim_ref.from)
+        .map(|store_ref| {
+            let store_ref = &store_ref.configurationStoreRef;
+            let kind = format!("{:?}", store_ref.kind);
+            let namespace = match store_ref.kind {
+                SupportedConfigurationStoreResourceType::ConfigurationStore => claim.namespace(),
+                SupportedConfigurationStoreResourceType::ClusterConfigurationStore => None,
+            };
+            (kind, namespace, store_ref.name.clone())
+        })
+        .collect()
+}
+
+/// Store reported as changed through webhook.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreChange {
+    pub kind: String,
+    /// `None` for ClusterConfigurationStore
+    pub namespace: Option<String>,
+    pub name: String,
+}
+
+impl Display for StoreChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.namespace {
+            Some(namespace) => write!(f, "{}/{}/{}", self.kind, namespace, self.name),
+            None => write!(f, "{}/{}", self.kind, self.name),
+        }
+    }
+}
+
+/// Delivers store changes announced by webhook to every claim controller.
+pub struct StoreChanges {
+    sender: broadcast::Sender<StoreChange>,
+}
+
+impl Default for StoreChanges {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(STORE_CHANGES_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl StoreChanges {
+    /// Returns `false` when no claim controller runs, e.g. on replica not holding leadership.
+    pub fn publish(&self, change: StoreChange) -> bool {
+        self.sender.send(change).is_ok()
+    }
+
+    pub fn subscribe(&self) -> impl Stream<Item = StoreChange> + Send + 'static {
+        stream::unfold(self.sender.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(change) => return Some((change, receiver)),
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Skipped {} store change notifications", skipped)
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+/// Claims whose target differs from the one written by their last sync, i.e. was modified or
+/// deleted by someone else. Target written by the claim itself matches resource version
+/// recorded by the sync, before status is updated too.
+pub fn claims_with_stale_target<T, Target>(
+    claims: &[Arc<T>],
+    target: &Target,
+    resync: &ResyncRequests,
+) -> Vec<ObjectRef<T>>
+where
+    T: Resource<DynamicType = ()> + HasClaimData + HasTarget,
+    Target: Resource<DynamicType = ()>,
+{
+    let name = target.name_any();
+    claims
+        .iter()
+        .filter(|claim| claim.namespace() == target.namespace())
+        .filter(|claim| {
+            let status = claim.get_claim_status();
+            let target_name = status
+                .and_then(|status| status.target_name.as_ref())
+                .unwrap_or(&claim.get_target().name);
+            let resource_version = target.meta().resource_version.as_ref();
+            *target_name == name
+                && status.and_then(|status| status.target_resource_version.as_ref())
+                    != resource_version
+                && !resync.written_by(claim.as_ref(), resource_version)
+        })
+        .map(|claim| ObjectRef::from_obj(claim.as_ref()))
+        .collect()
+}
+
+/// Maps target event to claims writing it, requesting their resync.
+pub fn target_trigger<T, Target>(
+    claims: Store<T>,
+    resync: Arc<ResyncRequests>,
+) -> impl Fn(Target) -> Vec<ObjectRef<T>> + Send + Sync + 'static
+where
+    T: Resource<DynamicType = ()> + HasClaimData + HasTarget + Clone + Send + Sync + 'static,
+    Target: Resource<DynamicType = ()>,
+{
+    move |target| {
+        let stale = claims_with_stale_target(&claims.state(), &target, &resync);
+        stale.iter().for_each(|claim| resync.request(claim));
+        stale
+    }
+}
+
+/// Maps store watch events to claims using stores which were created, deleted or whose spec
+/// changed, requesting their resync.
+pub fn store_trigger<T, S>(
+    events: impl Stream<Item = Result<Event<S>, watcher::Error>> + Send + 'static,
+    index: Arc<StoreClaims<T>>,
+    resync: Arc<ResyncRequests>,
+) -> impl Stream<Item = ObjectRef<T>> + Send + 'static
+where
+    T: Resource<DynamicType = ()> + HasClaimData + Send + Sync + 'static,
+    S: Resource<DynamicType = ()> + Send + 'static,
+{
+    let generations = StoreGenerations::<S>::default();
+    events
+        .filter_map(|event| async move {
+            event
+                .map_err(|e| warn!("Failed to watch {}: {:?}", S::kind(&()), e))
+                .ok()
+        })
+        .flat_map(move |event| {
+            let affected: Vec<_> = generations
+                .changed(&event)
+                .iter()
+                .flat_map(|store| index.claims_using(store))
+                .collect();
+            affected.iter().for_each(|claim| resync.request(claim));
+            stream::iter(affected)
+        })
+}
+
+/// Maps store changes announced by webhook to claims using the store, requesting their resync.
+pub fn store_change_trigger<T>(
+    changes: impl Stream<Item = StoreChange> + Send + 'static,
+    index: Arc<StoreClaims<T>>,
+    resync: Arc<ResyncRequests>,
+) -> impl Stream<Item = ObjectRef<T>> + Send + 'static
+where
+    T: Resource<DynamicType = ()> + HasClaimData + Send + Sync + 'static,
+{
+    changes.flat_map(move |change| {
+        let affected =
+            index.claims_referencing(&change.kind, change.namespace.as_deref(), &change.name);
+        affected.iter().for_each(|claim| resync.request(claim));
+        stream::iter(affected)
+    })
+}
@@ -0,0 +1,252 @@
+#[cfg(test)]
+mod tests {
+    use crate::controller::v1alpha1::claim_triggers::{
+        claims_with_stale_target, store_change_trigger, store_trigger, ResyncRequests, StoreChange,
+        StoreChanges, StoreClaims, StoreGenerations,
+    };
+    use crate::controller::v1alpha1::crd::claim::ConfigMapClaim;
+    use crate::controller::v1alpha1::crd::configuration_store::{
+        ClusterConfigurationStore, ConfigurationStore,
+    };
+    use futures::StreamExt;
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use kube::runtime::reflector::ObjectRef;
+    use kube::runtime::watcher::Event;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn claim(name: &str, namespace: &str, store_kind: &str, store: &str) -> Arc<ConfigMapClaim> {
+        Arc::new(
+            serde_json::from_value(json!({
+                "apiVersion": "external-config.com/v1alpha1",
+                "kind": "ConfigMapClaim",
+                "metadata": { "name": name, "namespace": namespace },
+                "spec": {
+                    "target": { "name": format!("{}-config", name), "creationPolicy": "Owned" },
+                    "data": {
+                        "app.json": {
+                            "from": [{
+                                "configurationStoreRef": { "name": store, "kind": store_kind }
+                            }]
+                        }
+                    }
+                },
+                "status": { "targetResourceVersion": "10" }
+            }))
+            .unwrap(),
+        )
+    }
+
+    fn provider_spec<T: serde::de::DeserializeOwned>() -> T {
+        serde_json::from_value(json!({ "provider": { "http": { "baseUrl": "http://store" } } }))
+            .unwrap()
+    }
+
+    fn store(name: &str, namespace: &str, generation: i64) -> ConfigurationStore {
+        let mut store = ConfigurationStore::new(name, provider_spec());
+        store.metadata.namespace = Some(namespace.to_string());
+        store.metadata.generation = Some(generation);
+        store
+    }
+
+    fn config_map(name: &str, namespace: &str, resource_version: &str) -> ConfigMap {
+        let mut config_map = ConfigMap::default();
+        config_map.metadata.name = Some(name.to_string());
+        config_map.metadata.namespace = Some(namespace.to_string());
+        config_map.metadata.resource_version = Some(resource_version.to_string());
+        config_map
+    }
+
+    fn names<K: kube::Resource<DynamicType = ()>>(refs: Vec<ObjectRef<K>>) -> Vec<String> {
+        let mut names: Vec<_> = refs.into_iter().map(|object| object.name).collect();
+        names.sort();
+        names
+    }
+
+    fn index(claims: &[Arc<ConfigMapClaim>]) -> Arc<StoreClaims<ConfigMapClaim>> {
+        let index = Arc::new(StoreClaims::default());
+        index.apply_watcher_event(&Event::Init);
+        for claim in claims {
+            index.apply_watcher_event(&Event::InitApply((**claim).clone()));
+        }
+        index.apply_watcher_event(&Event::InitDone);
+        index
+    }
+
+    #[test]
+    fn test_store_maps_to_claims_referencing_it() {
+        let index = index(&[
+            claim("a", "default", "ConfigurationStore", "store"),
+            claim("b", "other", "ConfigurationStore", "store"),
+            claim("c", "default", "ClusterConfigurationStore", "store"),
+            claim("d", "other", "ClusterConfigurationStore", "store"),
+            claim("e", "default", "ConfigurationStore", "another"),
+        ]);
+
+        assert_eq!(
+            names(index.claims_using(&ObjectRef::from_obj(&store("store", "default", 1)))),
+            vec!["a"]
+        );
+
+        let cluster_store = ClusterConfigurationStore::new("store", provider_spec());
+        assert_eq!(
+            names(index.claims_using(&ObjectRef::from_obj(&cluster_store))),
+            vec!["c", "d"]
+        );
+    }
+
+    #[test]
+    fn test_store_index_follows_claim_changes() {
+        let index = index(&[claim("a", "default", "ConfigurationStore", "store")]);
+        let referencing = |name: &str| {
+            names(index.claims_referencing("ConfigurationStore", Some("default"), name))
+        };
+
+        let moved = claim("a", "default", "ConfigurationStore", "another");
+        index.apply_watcher_event(&Event::Apply((*moved).clone()));
+        assert!(referencing("store").is_empty());
+        assert_eq!(referencing("another"), vec!["a"]);
+
+        index.apply_watcher_event(&Event::Delete((*moved).clone()));
+        assert!(referencing("another").is_empty());
+
+        // Relist replaces index once done, claim deleted meanwhile is dropped
+        index.apply_watcher_event(&Event::Apply((*moved).clone()));
+        index.apply_watcher_event(&Event::Init);
+        index.apply_watcher_event(&Event::InitApply(
+            (*claim("b", "default", "ConfigurationStore", "store")).clone(),
+        ));
+        assert_eq!(referencing("another"), vec!["a"]);
+        index.apply_watcher_event(&Event::InitDone);
+        assert!(referencing("another").is_empty());
+        assert_eq!(referencing("store"), vec!["b"]);
+    }
+
+    #[test]
+    fn test_only_store_spec_change_counts() {
+        let generations = StoreGenerations::default();
+        let changed = |event: Event<ConfigurationStore>| names(generations.changed(&event));
+
+        assert!(changed(Event::Init).is_empty());
+        // Stores listed on startup are not changes
+        assert!(changed(Event::InitApply(store("store", "default", 1))).is_empty());
+        assert!(changed(Event::InitDone).is_empty());
+        // Status update keeps generation
+        assert!(changed(Event::Apply(store("store", "default", 1))).is_empty());
+        assert_eq!(
+            changed(Event::Apply(store("store", "default", 2))),
+            vec!["store"]
+        );
+    }
+
+    #[test]
+    fn test_created_and_deleted_stores_count() {
+        let generations = StoreGenerations::default();
+        let changed = |event: Event<ConfigurationStore>| names(generations.changed(&event));
+        changed(Event::Init);
+        changed(Event::InitApply(store("kept", "default", 1)));
+        changed(Event::InitApply(store("deleted", "default", 1)));
+        changed(Event::InitDone);
+
+        assert_eq!(
+            changed(Event::Apply(store("created", "default", 1))),
+            vec!["created"]
+        );
+        assert_eq!(
+            changed(Event::Delete(store("created", "default", 1))),
+            vec!["created"]
+        );
+
+        // Relist after watch restarted reports what happened meanwhile
+        changed(Event::Init);
+        assert!(changed(Event::InitApply(store("kept", "default", 1))).is_empty());
+        assert_eq!(
+            changed(Event::InitApply(store("new", "default", 1))),
+            vec!["new"]
+        );
+        assert_eq!(changed(Event::InitDone), vec!["deleted"]);
+    }
+
+    #[tokio::test]
+    async fn test_store_created_after_startup_resyncs_claims_using_it() {
+        let index = index(&[claim("a", "default", "ConfigurationStore", "store")]);
+        let resync = Arc::new(ResyncRequests::default());
+        let events = futures::stream::iter(vec![
+            Ok(Event::Init),
+            Ok(Event::InitDone),
+            Ok(Event::Apply(store("store", "default", 1))),
+        ]);
+
+        let triggered: Vec<_> = store_trigger(events, index, resync.clone()).collect().await;
+
+        assert_eq!(names(triggered), vec!["a"]);
+        assert!(resync.take(claim("a", "default", "ConfigurationStore", "store").as_ref()));
+    }
+
+    #[test]
+    fn test_target_modified_by_others_maps_to_its_claim() {
+        let claims = vec![
+            claim("a", "default", "ConfigurationStore", "store"),
+            claim("b", "default", "ConfigurationStore", "store"),
+        ];
+        let resync = ResyncRequests::default();
+        let stale = |target: &ConfigMap| names(claims_with_stale_target(&claims, target, &resync));
+
+        assert!(stale(&config_map("a-config", "default", "10")).is_empty());
+        assert_eq!(stale(&config_map("a-config", "default", "11")), vec!["a"]);
+        assert!(stale(&config_map("a-config", "other", "11")).is_empty());
+    }
+
+    #[test]
+    fn test_target_written_by_claim_before_status_update_is_not_stale() {
+        let claims = vec![claim("a", "default", "ConfigurationStore", "store")];
+        let resync = ResyncRequests::default();
+        let stale = |target: &ConfigMap| names(claims_with_stale_target(&claims, target, &resync));
+
+        // Sync wrote target, its watch event arrives while status still holds version 10
+        resync.record_written(claims[0].as_ref(), Some("11"));
+        assert!(stale(&config_map("a-config", "default", "11")).is_empty());
+        assert!(!resync.take(claims[0].as_ref()));
+
+        // Change by someone else after the write is still noticed
+        assert_eq!(stale(&config_map("a-config", "default", "12")), vec!["a"]);
+    }
+
+    #[test]
+    fn test_resync_request_is_taken_once() {
+        let resync = ResyncRequests::default();
+        let claim = claim("a", "default", "ConfigurationStore", "store");
+
+        resync.request(&ObjectRef::from_obj(claim.as_ref()));
+        assert!(resync.take(claim.as_ref()));
+        assert!(!resync.take(claim.as_ref()));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_store_change_resyncs_claims_using_store() {
+        let claims = index(&[
+            claim("a", "default", "ConfigurationStore", "store"),
+            claim("b", "default", "ConfigurationStore", "another"),
+        ]);
+        let changes = StoreChanges::default();
+        let resync = Arc::new(ResyncRequests::default());
+        let change = StoreChange {
+            kind: "ConfigurationStore".to_string(),
+            namespace: Some("default".to_string()),
+            name: "store".to_string(),
+        };
+
+        // Nothing consumes changes until controller subscribes
+        assert!(!changes.publish(change.clone()));
+        let mut triggered = Box::pin(store_change_trigger(
+            changes.subscribe(),
+            claims,
+            resync.clone(),
+        ));
+        assert!(changes.publish(change));
+
+        let claim_ref = triggered.next().await.unwrap();
+        assert_eq!(claim_ref.name, "a");
+        assert!(resync.take(claim("a", "default", "ConfigurationStore", "store").as_ref()));
+    }
+}
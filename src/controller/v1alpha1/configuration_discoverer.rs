@@ -6,36 +6,55 @@ use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use super::crd::claim::{
-    ClaimRef, ClaimRefParametrization, ConfigInjectionStrategy, ConfigMapClaim, SecretClaim,
-    SupportedConfigurationStoreResourceType,
+    ClaimCreationPolicy, ClaimDataFormat, ClaimRef, ClaimRefParametrization,
+    ConfigInjectionStrategy, ConfigMapClaim, SecretClaim, SupportedConfigurationStoreResourceType,
 };
-use crate::contract::clients::ICrdClient;
+use crate::contract::clients::{ICrdClient, K8sClient};
+use crate::contract::iconfigstore::{ConfigPayload, IConfigStore};
+use crate::controller::config_store::content_cache::ParsedContentKey;
 use crate::controller::utils::file_format::{
-    convert_to_format, convert_to_json, merge_configs, to_file_type, to_file_type_from_filename,
-    ConfigFileType, ConfigFormat,
+    apply_json_patch, convert_to_format, convert_to_json, merge_configs_with, to_file_type,
+    to_file_type_from_content_type, to_file_type_from_filename, ConfigFileType, ConfigFormat,
 };
-use crate::controller::utils::parsers::text_to_json::try_parse_file_to_json;
+use crate::controller::utils::parsers::text_to_json::parse_file;
+use crate::controller::utils::selector::{explode, scalar_to_string, select};
+use crate::controller::utils::template::render_template;
+use chrono::Utc;
 use k8s_openapi::api::core::v1::{ConfigMap, Secret};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ObjectMeta, OwnerReference, Time};
 use k8s_openapi::{ByteString, NamespaceResourceScope};
-use kube::api::{Patch, PatchParams, PostParams};
+use kube::api::{DeleteParams, ListParams, Patch, PatchParams, PostParams};
+use kube::core::ErrorResponse;
 use kube::runtime::controller::Action;
 use kube::runtime::events::EventType;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_json::json;
-use tracing::log::info;
+use sha2::{Digest, Sha256};
+use std::fmt::Debug;
+use tracing::log::{info, warn};
 
 use crate::contract::ireconcilable::{IReconcilable, ReconcilableTargetTypeBounds};
 use crate::contract::lib::{Error, Result};
 use crate::controller::controller::DOCUMENT_FINALIZER;
 use crate::controller::utils::context::Context;
 use crate::controller::utils::crd::HasData;
-use crate::controller::v1alpha1::crd::claim::{HasTarget, Refreshable};
+use crate::controller::v1alpha1::claim_status::{
+    claim_status, data_key_status, next_sync_in, unchanged_target, SyncedTarget,
+};
+use crate::controller::v1alpha1::crd::claim::{
+    DataKeyStatus, HasClaimData, HasTarget, Refreshable,
+};
 use crate::controller::v1alpha1::crd::configuration_store::{
     ClusterConfigurationStore, ConfigurationStore, Provider,
 };
+use crate::controller::v1alpha1::rollout::{
+    rollout_annotation, rollout_required, rollout_workloads,
+};
 
 #[async_trait]
 pub trait ConfigurationDiscoverer<TargetType>:
-    IReconcilable + Sized + HasTarget + Refreshable
+    IReconcilable + Sized + HasTarget + HasClaimData + Refreshable + Clone + Debug + DeserializeOwned
 where
     TargetType: ReconcilableTargetTypeBounds,
 {
@@ -45,8 +64,13 @@ where
         claim_ref: &ClaimRef,
         namespace: &str,
         file: &str,
-        data: &mut BTreeMap<String, String>,
-    ) -> Result<(), Error> {
+        data: &mut ComposedData,
+    ) -> Result<Vec<String>, Error> {
+        if claim_ref.format == Some(ClaimDataFormat::Raw) {
+            return self
+                .apply_raw_format(ctx, claim_ref, namespace, file, data)
+                .await;
+        }
         match &claim_ref
             .strategy
             .as_ref()
@@ -68,14 +92,15 @@ where
         claim_ref: &ClaimRef,
         namespace: &str,
         file: &str,
-        data: &mut BTreeMap<String, String>,
-    ) -> Result<(), Error> {
+        data: &mut ComposedData,
+    ) -> Result<Vec<String>, Error> {
         let mut merged_config: Option<ConfigFormat> = None;
-        let mut first_file_format: Option<ConfigFileType> = to_file_type_from_filename(file);
+        let mut first_file_format: Option<ConfigFileType> = output_file_type(claim_ref, file);
+        let merge_options = claim_ref.merge_options.clone().unwrap_or_default();
 
         for store_ref in &claim_ref.from {
             let result = self
-                .process_store_ref(ctx.clone(), store_ref, namespace, file)
+                .process_store_ref(ctx.clone(), store_ref, namespace, file, data)
                 .await?;
 
             if first_file_format.is_none() {
@@ -83,21 +108,32 @@ where
             }
 
             merged_config = Some(if let Some(existing) = merged_config {
-                merge_configs(existing, result)?
+                merge_configs_with(existing, result, &merge_options)?
             } else {
                 result
             });
         }
 
-        if let Some(merged) = merged_config {
+        if let Some(mut merged) = merged_config {
+            if let (ConfigFormat::Json(document), Some(patch)) =
+                (&mut merged, merge_options.patch.as_ref())
+            {
+                apply_json_patch(document, patch)?;
+            }
             if let Some(file_format) = first_file_format {
-                let final_result = convert_to_format(&merged, &file_format)?;
-                data.insert(file.to_string(), final_result);
+                data.data.extend(
+                    self.file_entries(ctx, claim_ref, namespace, file, &merged, &file_format)
+                        .await?,
+                );
             } else {
                 return Err(Error::UnsupportedFileType());
             }
         }
-        Ok(())
+        Ok(claim_ref
+            .from
+            .iter()
+            .map(|store_ref| store_ref.configurationStoreRef.to_string())
+            .collect())
     }
 
     async fn apply_fallback_strategy(
@@ -106,87 +142,537 @@ where
         claim_ref: &ClaimRef,
         namespace: &str,
         file: &str,
-        data: &mut BTreeMap<String, String>,
-    ) -> Result<(), Error> {
+        data: &mut ComposedData,
+    ) -> Result<Vec<String>, Error> {
+        let file_format = output_file_type(claim_ref, file).unwrap_or(ConfigFileType::Json);
+        let mut failures = Vec::new();
+
         for store_ref in &claim_ref.from {
-            if let Ok(file_data) = self
-                .process_store_ref(ctx.clone(), store_ref, namespace, file)
+            match self
+                .process_store_ref(ctx.clone(), store_ref, namespace, file, data)
                 .await
             {
-                data.insert(
-                    file.to_string(),
-                    convert_to_format(&file_data, &ConfigFileType::Json)?,
-                );
-                return Ok(());
+                Ok(file_data) => {
+                    data.data.extend(
+                        self.file_entries(
+                            ctx,
+                            claim_ref,
+                            namespace,
+                            file,
+                            &file_data,
+                            &file_format,
+                        )
+                        .await?,
+                    );
+                    return Ok(vec![store_ref.configurationStoreRef.to_string()]);
+                }
+                Err(e) => {
+                    // Data may now come from another store than on previous sync
+                    data.changed = true;
+                    failures.push((store_ref.configurationStoreRef.to_string(), e))
+                }
+            }
+        }
+        Err(stores_failed(failures))
+    }
+    /// Entries written for data key, rendered from template when claim ref has one.
+    async fn file_entries(
+        &self,
+        ctx: Arc<Context>,
+        claim_ref: &ClaimRef,
+        namespace: &str,
+        file: &str,
+        config: &ConfigFormat,
+        file_type: &ConfigFileType,
+    ) -> Result<Vec<(String, String)>, Error> {
+        let Some(template) = &claim_ref.template else {
+            return select_entries(claim_ref, file, config, file_type);
+        };
+        let source = match (&template.inline, &template.config_map_ref) {
+            (Some(inline), _) => inline.clone(),
+            (None, Some(config_map_ref)) => ctx
+                .v1alpha1
+                .get_config_map(&config_map_ref.name, namespace)
+                .await?
+                .data
+                .and_then(|mut data| data.remove(&config_map_ref.key))
+                .ok_or_else(|| {
+                    Error::TemplateError(format!(
+                        "Key {} not found in ConfigMap {}/{}",
+                        config_map_ref.key, namespace, config_map_ref.name
+                    ))
+                })?,
+            (None, None) => {
+                return Err(Error::TemplateError(String::from(
+                    "template needs inline source or configMapRef",
+                )))
+            }
+        };
+
+        let ConfigFormat::Json(document) = convert_to_json(config)? else {
+            return Err(Error::UnsupportedFileType());
+        };
+        let context = select(&document, claim_ref.selector.as_deref().unwrap_or(""))?;
+        Ok(vec![(file.to_string(), render_template(&source, context)?)])
+    }
+
+    /// Writes payload of first store serving it untouched, decoded from base64 when requested.
+    async fn apply_raw_format(
+        &self,
+        ctx: Arc<Context>,
+        claim_ref: &ClaimRef,
+        namespace: &str,
+        file: &str,
+        data: &mut ComposedData,
+    ) -> Result<Vec<String>, Error> {
+        let mut failures = Vec::new();
+
+        for store_ref in &claim_ref.from {
+            match self
+                .fetch_store_ref(ctx.clone(), store_ref, namespace, data)
+                .await
+            {
+                Ok((payload, _)) => {
+                    let payload = if claim_ref.base64_decode.unwrap_or(false) {
+                        decode_base64_payload(&payload.body)?
+                    } else {
+                        payload.body
+                    };
+                    data.binary_data.insert(file.to_string(), payload);
+                    return Ok(vec![store_ref.configurationStoreRef.to_string()]);
+                }
+                Err(e) => {
+                    data.changed = true;
+                    failures.push((store_ref.configurationStoreRef.to_string(), e))
+                }
             }
         }
-        Err(Error::ConfigStoreError())
+        Err(stores_failed(failures))
     }
+
     async fn process_store_ref(
         &self,
         ctx: Arc<Context>,
         store_ref: &ClaimRefParametrization,
         namespace: &str,
         file: &str,
+        data: &mut ComposedData,
     ) -> Result<ConfigFormat, Error> {
-        let provider = match store_ref.configurationStoreRef.kind {
+        let (payload, store) = self
+            .fetch_store_ref(ctx.clone(), store_ref, namespace, data)
+            .await?;
+        // Document parsed from the same revision is reused instead of parsing it again
+        let cache_key = parsed_content_key(&store, store_ref, namespace);
+        let revision = payload.revision.as_deref();
+        if let Some(config) = ctx.parsed_content.get(&cache_key, revision) {
+            return Ok(config);
+        }
+
+        let content_type_hint = payload
+            .content_type
+            .as_deref()
+            .and_then(to_file_type_from_content_type);
+
+        let parsed_config = parse_file(
+            &String::from_utf8_lossy(&payload.body),
+            store_ref.input_format.as_ref(),
+            content_type_hint.as_ref(),
+        )?;
+        let config = convert_to_json(&parsed_config)?;
+        ctx.parsed_content.insert(&cache_key, revision, &config);
+        Ok(config)
+    }
+
+    /// Fetches store payload, marking data as changed unless store confirmed it unchanged.
+    /// Metadata of the store serving it is returned along.
+    async fn fetch_store_ref(
+        &self,
+        ctx: Arc<Context>,
+        store_ref: &ClaimRefParametrization,
+        namespace: &str,
+        data: &mut ComposedData,
+    ) -> Result<(ConfigPayload, ObjectMeta), Error> {
+        let params = store_ref.configurationStoreParams.clone();
+        let (payload, store) = match store_ref.configurationStoreRef.kind {
             SupportedConfigurationStoreResourceType::ClusterConfigurationStore => {
                 let store = ctx
                     .v1alpha1
                     .get_cluster_config_store(&store_ref.configurationStoreRef.name)
                     .await?;
-                store.spec.provider
+                let config_store = store.get_config_store(&ctx).await?;
+                let result = config_store.get_payload(params, None).await;
+                store.report_circuit_state(&ctx, config_store.state()).await;
+                (result?, store.metadata)
             }
             SupportedConfigurationStoreResourceType::ConfigurationStore => {
                 let store = ctx
                     .v1alpha1
                     .get_config_store(&store_ref.configurationStoreRef.name, namespace)
                     .await?;
-                store.spec.provider
+                let config_store = store.get_config_store(&ctx).await?;
+                let result = config_store.get_payload(params, None).await;
+                store.report_circuit_state(&ctx, config_store.state()).await;
+                (result?, store.metadata)
             }
         };
 
-        let config_store = provider.get_config_store();
-        let file = config_store
-            .get_config(store_ref.configurationStoreParams.clone(), None)
-            .await?;
+        if payload.not_modified || payload.revision.is_some() {
+            let store = &store_ref.configurationStoreRef;
+            let store_namespace = match store.kind {
+                SupportedConfigurationStoreResourceType::ConfigurationStore => namespace,
+                SupportedConfigurationStoreResourceType::ClusterConfigurationStore => "",
+            };
+            ctx.metrics.config_store.record_cache_lookup(
+                &format!("{:?}", store.kind),
+                store_namespace,
+                &store.name,
+                payload.not_modified,
+            );
+        }
+        data.changed |= !payload.not_modified;
+        Ok((payload, store))
+    }
 
-        let parsed_config = try_parse_file_to_json(&file)?;
-        convert_to_json(&parsed_config)
+    /// Composes every data key of the claim. All keys are attempted so each of them gets
+    /// its sync result in status, first error is returned.
+    async fn compose_data(
+        &self,
+        ctx: Arc<Context>,
+        namespace: &str,
+        keys: &mut Vec<DataKeyStatus>,
+    ) -> Result<ComposedData, Error> {
+        let mut data = ComposedData::default();
+        let mut first_error: Option<Error> = None;
+        let mut files: Vec<_> = self.get_claim_data().iter().collect();
+        files.sort_by_key(|(file, _)| *file);
+
+        for (file, claim_ref) in files {
+            let mut file_data = ComposedData::default();
+            let result = self
+                .compose_file(ctx.clone(), claim_ref, namespace, file, &mut file_data)
+                .await
+                .and_then(|stores| match duplicate_key(&data, &file_data) {
+                    Some(key) => Err(Error::DuplicateDataKey(key)),
+                    None => Ok(stores),
+                });
+            let content = composed_content(file, &file_data);
+            keys.push(data_key_status(
+                self.get_claim_status(),
+                file,
+                result
+                    .as_ref()
+                    .map(|stores| (stores.as_slice(), content.as_slice())),
+                Utc::now(),
+            ));
+            // Colliding key is not written, so it does not overwrite content of another one
+            if !matches!(result, Err(Error::DuplicateDataKey(_))) {
+                data.data.extend(file_data.data);
+                data.binary_data.extend(file_data.binary_data);
+            }
+            data.changed |= file_data.changed;
+            if let Err(e) = result {
+                first_error.get_or_insert(e);
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(data),
+        }
     }
 
-    async fn reconcile(&self, ctx: Arc<Context>) -> Result<Action> {
+    /// Each claim applies its keys under own field manager, so claims sharing target
+    /// and other managers of the target do not take over each other's keys.
+    fn apply_params(&self) -> PatchParams {
+        let params = PatchParams::apply(&field_manager(&Self::kind(&()), &self.name_any()));
+        if self.get_target().force_conflicts.unwrap_or(false) {
+            params.force()
+        } else {
+            params
+        }
+    }
+
+    /// Claim is controller owner of the target only with `Owned` creation policy.
+    fn target_owner_references(&self) -> Option<Vec<OwnerReference>> {
+        match self.get_target().creationPolicy {
+            ClaimCreationPolicy::Owned => Some(vec![self.controller_owner_ref(&()).unwrap()]),
+            _ => None,
+        }
+    }
+
+    /// Immutable targets are named after hash of their content, so changed content creates
+    /// new object.
+    fn target_name(&self, hash: &str) -> String {
+        let target = self.get_target();
+        if target.immutable.unwrap_or(false) {
+            immutable_target_name(&target.name, hash)
+        } else {
+            target.name.clone()
+        }
+    }
+
+    fn target_metadata(&self) -> ObjectMeta {
+        let target = self.get_target();
+        let template = target
+            .template
+            .as_ref()
+            .and_then(|template| template.metadata.clone())
+            .unwrap_or_default();
+        let mut labels = template.labels.unwrap_or_default();
+        if target.immutable.unwrap_or(false) {
+            labels.insert(
+                CLAIM_UID_LABEL.to_string(),
+                self.meta().uid.clone().unwrap_or_default(),
+            );
+        }
+
+        ObjectMeta {
+            name: Some(target.name.clone()),
+            namespace: <Self as ResourceExt>::namespace(self),
+            owner_references: self.target_owner_references(),
+            labels: Some(labels).filter(|labels| !labels.is_empty()),
+            annotations: template.annotations,
+            ..Default::default()
+        }
+    }
+
+    /// Writes composed data into target according to its creation policy.
+    async fn sync_target(
+        &self,
+        ctx: Arc<Context>,
+        keys: &mut Vec<DataKeyStatus>,
+        resync_requested: bool,
+    ) -> Result<SyncedTarget> {
         let client = ctx.client.clone();
         let namespace = <Self as ResourceExt>::namespace(self).unwrap();
-        let name = self.name_any();
 
         let resources: Api<TargetType> = Api::namespaced((*client).clone(), &namespace);
-        info!("Reconciling resource: {} in namespace: {}", name, namespace);
         let target = self.get_target();
+        let data = self.compose_data(ctx.clone(), &namespace, keys).await?;
 
-        match resources.get(&target.name).await {
+        // Stores confirmed content unchanged, target written by last sync is left as it is.
+        // Templates from ConfigMap are not revalidated, so they always go through diff
+        let templates_from_config_map = self.get_claim_data().values().any(|claim_ref| {
+            claim_ref
+                .template
+                .as_ref()
+                .is_some_and(|template| template.config_map_ref.is_some())
+        });
+        if !data.changed && !resync_requested && !templates_from_config_map {
+            if let Some(synced) = unchanged_target(self.get_claim_status(), self.meta().generation)
+            {
+                return Ok(synced);
+            }
+        }
+        // Hash covers target as written, i.e. also key mapping and type of Secret
+        let mut desired_resource = self.create_resource_spec(ctx.clone(), data).await?;
+        let hash = content_hash(&desired_resource);
+        let name = self.target_name(&hash);
+        desired_resource.meta_mut().name = Some(name.clone());
+
+        let resource_version = match resources.get(&name).await {
             Ok(existing_resource) => {
-                let desired_resource = self.create_resource_spec(ctx.clone()).await?;
+                let generation_synced = self
+                    .get_claim_status()
+                    .and_then(|status| status.observed_generation)
+                    == self.meta().generation;
+
+                // Targets written before per-claim field managers are taken over once
+                let legacy_managed = managed_by(existing_resource.meta(), LEGACY_FIELD_MANAGER);
 
-                if existing_resource.get_data() != desired_resource.get_data() {
+                if !legacy_managed
+                    && generation_synced
+                    && target_data_synced(
+                        existing_resource.get_data().as_ref(),
+                        desired_resource.get_data().as_ref(),
+                    )
+                    && target_data_synced(
+                        existing_resource.get_binary_data().as_ref(),
+                        desired_resource.get_binary_data().as_ref(),
+                    )
+                {
+                    existing_resource.resource_version()
+                } else {
+                    // Server side apply only touches fields in desired resource,
+                    // keys not claimed are left to their field managers
                     let patch = Patch::Apply(json!(&desired_resource));
-                    let params = PatchParams::apply("configmap-claim-controller").force();
-                    resources
-                        .patch(&target.name, &params, &patch)
+                    let params = if legacy_managed {
+                        self.apply_params().force()
+                    } else {
+                        self.apply_params()
+                    };
+                    let applied = resources
+                        .patch(&name, &params, &patch)
                         .await
-                        .map_err(Error::KubeError)?;
+                        .map_err(map_apply_error)?
+                        .resource_version();
+                    if legacy_managed {
+                        self.release_legacy_fields(&resources, &name).await?
+                    } else {
+                        applied
+                    }
                 }
             }
             Err(kube::Error::Api(ref e)) if e.code == 404 => {
-                let new_resource = self.create_resource_spec(ctx.clone()).await?;
+                if !creates_target(&target.creationPolicy) {
+                    return Err(Error::TargetNotFound(name));
+                }
                 resources
-                    .create(&PostParams::default(), &new_resource)
+                    .patch(
+                        &name,
+                        &self.apply_params(),
+                        &Patch::Apply(json!(&desired_resource)),
+                    )
                     .await
-                    .map_err(Error::KubeError)?;
+                    .map_err(map_apply_error)?
+                    .resource_version()
             }
             Err(e) => return Err(Error::KubeError(e)),
+        };
+        // Watch event of the write must not be taken for change made by someone else
+        ctx.claim_resync
+            .record_written(self, resource_version.as_deref());
+
+        if target.immutable.unwrap_or(false) {
+            self.prune_immutable_targets(&resources, &name).await?;
+        }
+
+        let rollout_hash = match target.rollout_targets.as_deref() {
+            Some(rollout_targets) if !rollout_targets.is_empty() => {
+                let previous_hash = self
+                    .get_claim_status()
+                    .and_then(|status| status.rollout_hash.as_deref());
+                if rollout_required(previous_hash, &hash) {
+                    let annotation = rollout_annotation(&Self::kind(&()), &self.name_any());
+                    rollout_workloads(&client, &namespace, rollout_targets, &annotation, &hash)
+                        .await?;
+                }
+                Some(hash)
+            }
+            _ => None,
+        };
+        Ok(SyncedTarget {
+            name,
+            resource_version,
+            rollout_hash,
+        })
+    }
+
+    /// Applies empty object as legacy field manager, so it owns nothing. Keys it alone owned,
+    /// i.e. ones no longer claimed, are removed, claimed keys stay with claim's field manager.
+    async fn release_legacy_fields(
+        &self,
+        resources: &Api<TargetType>,
+        name: &str,
+    ) -> Result<Option<String>> {
+        info!(
+            "Releasing fields of {} held by {}",
+            name, LEGACY_FIELD_MANAGER
+        );
+        let empty = json!({
+            "apiVersion": TargetType::api_version(&()),
+            "kind": TargetType::kind(&()),
+            "metadata": { "name": name },
+        });
+        let released = resources
+            .patch(
+                name,
+                &PatchParams::apply(LEGACY_FIELD_MANAGER),
+                &Patch::Apply(empty),
+            )
+            .await
+            .map_err(Error::KubeError)?;
+        Ok(released.resource_version())
+    }
+
+    /// Deletes immutable targets of previous content beyond history limit, oldest first.
+    async fn prune_immutable_targets(
+        &self,
+        resources: &Api<TargetType>,
+        current: &str,
+    ) -> Result<(), Error> {
+        let Some(uid) = self.meta().uid.as_ref() else {
+            return Ok(());
+        };
+        let previous = resources
+            .list(&ListParams::default().labels(&format!("{}={}", CLAIM_UID_LABEL, uid)))
+            .await
+            .map_err(Error::KubeError)?
+            .items
+            .iter()
+            .map(|resource| {
+                (
+                    resource.name_any(),
+                    resource.meta().creation_timestamp.clone(),
+                )
+            })
+            .collect();
+        let history_limit = self
+            .get_target()
+            .history_limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT);
+
+        for name in targets_to_prune(current, previous, history_limit as usize) {
+            info!("Deleting previous immutable target {}", name);
+            match resources.delete(&name, &DeleteParams::default()).await {
+                Ok(_) | Err(kube::Error::Api(ErrorResponse { code: 404, .. })) => {}
+                Err(e) => return Err(Error::KubeError(e)),
+            }
+        }
+        Ok(())
+    }
+
+    async fn reconcile(&self, ctx: Arc<Context>) -> Result<Action> {
+        let client = ctx.client.clone();
+        let namespace = <Self as ResourceExt>::namespace(self).unwrap();
+        let name = self.name_any();
+        let generation = self.meta().generation;
+
+        // Change of target or store requests sync before refresh interval passes
+        let resync_requested = ctx.claim_resync.take(self);
+        if !resync_requested {
+            if let Some(remaining) = next_sync_in(
+                self.get_claim_status(),
+                generation,
+                self.get_refresh_interval(),
+                Utc::now(),
+            ) {
+                return Ok(Action::requeue(remaining));
+            }
         }
 
+        info!("Reconciling resource: {} in namespace: {}", name, namespace);
+        let mut keys = Vec::new();
+        let result = self
+            .sync_target(ctx.clone(), &mut keys, resync_requested)
+            .await;
+
+        let status = claim_status(
+            self.get_claim_status(),
+            generation,
+            keys,
+            &result,
+            Utc::now(),
+        );
+        // Unchanged status is not written, so repeated failures do not trigger reconciles
+        let status_result = if self.get_claim_status() == Some(&status) {
+            Ok(())
+        } else {
+            self.claim_api((*client).clone())
+                .patch_status(
+                    &name,
+                    &PatchParams::default(),
+                    &Patch::Merge(json!({ "status": status })),
+                )
+                .await
+                .map(|_| ())
+        };
+
+        result?;
+        if let Err(e) = status_result {
+            warn!("Failed to update status of {}: {}", name, e);
+            return Err(Error::KubeError(e));
+        }
         Ok(Action::requeue(self.get_refresh_interval()))
     }
 
@@ -194,9 +680,27 @@ where
         let client = ctx.client.clone();
         let namespace = <Self as ResourceExt>::namespace(self).unwrap();
         let target = self.get_target();
-        let name = &target.name;
+        // Immutable target is written under name recorded in status
+        let name = self
+            .get_claim_status()
+            .and_then(|status| status.target_name.as_ref())
+            .unwrap_or(&target.name);
 
         let resources: Api<TargetType> = Api::namespaced((*client).clone(), &namespace);
+        if target.creationPolicy == ClaimCreationPolicy::Merge {
+            // Applying resource without data releases claimed keys, which removes them
+            // unless other field manager holds them too
+            let mut released = TargetType::default();
+            released.meta_mut().name = Some(name.clone());
+            released.meta_mut().namespace = Some(namespace.clone());
+            match resources
+                .patch(name, &self.apply_params(), &Patch::Apply(json!(&released)))
+                .await
+            {
+                Ok(_) | Err(kube::Error::Api(ErrorResponse { code: 404, .. })) => {}
+                Err(e) => return Err(Error::KubeError(e)),
+            }
+        }
         if let Ok(mut resource) = resources.get(name).await {
             if let Some(ref mut finalizers) = resource.meta_mut().finalizers {
                 finalizers.retain(|f| f != DOCUMENT_FINALIZER);
@@ -210,5 +714,241 @@ where
         Ok(Action::await_change())
     }
 
-    async fn create_resource_spec(&self, ctx: Arc<Context>) -> Result<TargetType, Error>;
+    async fn create_resource_spec(
+        &self,
+        ctx: Arc<Context>,
+        data: ComposedData,
+    ) -> Result<TargetType, Error>;
+}
+
+/// Data composed for target. Raw data keys hold payload passed through from stores untouched.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComposedData {
+    pub data: BTreeMap<String, String>,
+    pub binary_data: BTreeMap<String, Vec<u8>>,
+    /// Some store served content it did not confirm unchanged since previous fetch
+    pub changed: bool,
+}
+
+impl ComposedData {
+    /// All entries as bytes, as Secret holds them.
+    pub fn into_bytes(self) -> BTreeMap<String, Vec<u8>> {
+        self.data
+            .into_iter()
+            .map(|(key, value)| (key, value.into_bytes()))
+            .chain(self.binary_data)
+            .collect()
+    }
+}
+
+/// Identifies document parsed from store response, which depends on store instance, its
+/// params and input format.
+pub fn parsed_content_key(
+    store: &ObjectMeta,
+    store_ref: &ClaimRefParametrization,
+    namespace: &str,
+) -> ParsedContentKey {
+    let params: BTreeMap<_, _> = store_ref
+        .configurationStoreParams
+        .iter()
+        .flatten()
+        .collect();
+    ParsedContentKey {
+        store_uid: store
+            .uid
+            .clone()
+            .unwrap_or_else(|| store.name.clone().unwrap_or_default()),
+        generation: store.generation,
+        request: format!(
+            "{}/{}?{:?}#{:?}",
+            namespace, store_ref.configurationStoreRef, params, store_ref.input_format
+        ),
+    }
+}
+
+/// Format data key is written in, explicit output format or one implied by its extension.
+pub fn output_file_type(claim_ref: &ClaimRef, file: &str) -> Option<ConfigFileType> {
+    claim_ref
+        .output_format
+        .clone()
+        .or_else(|| to_file_type_from_filename(file))
+}
+
+/// Error of data key none of the stores could serve, naming each store with its error.
+pub fn stores_failed(failures: Vec<(String, Error)>) -> Error {
+    Error::ConfigStoresFailed(
+        failures
+            .iter()
+            .map(|(store, e)| format!("{}: {}", store, e))
+            .collect::<Vec<_>>()
+            .join("; "),
+    )
+}
+
+/// Base64 payload may be wrapped over multiple lines, whitespace is ignored.
+pub fn decode_base64_payload(payload: &[u8]) -> Result<Vec<u8>, Error> {
+    let encoded: Vec<u8> = payload
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    STANDARD.decode(encoded).map_err(Error::Base64DecodeError)
+}
+
+/// Labels immutable targets of the claim, so previous ones can be found and pruned.
+pub static CLAIM_UID_LABEL: &str = "external-config.com/claim-uid";
+pub static DEFAULT_HISTORY_LIMIT: u32 = 2;
+
+/// Target name suffixed with digest of its content, e.g. `app-config-3f2a9c0d1e`.
+pub fn immutable_target_name(name: &str, hash: &str) -> String {
+    format!("{}-{}", name, &hash[..10])
+}
+
+/// Hex encoded SHA-256 digest of target without its metadata, i.e. of data, binary data
+/// and type of Secret.
+pub fn content_hash<T: Serialize>(target: &T) -> String {
+    let mut content = serde_json::to_value(target).unwrap_or_default();
+    if let Some(content) = content.as_object_mut() {
+        content.remove("metadata");
+    }
+    format!("{:x}", Sha256::digest(content.to_string()))
+}
+
+/// Entries written for data key `file`: whole document in format of the key, or part of it
+/// picked by selector, which is written as plain value when scalar. Exploded selection
+/// writes each leaf under its own key instead, prefixed by `file`.
+pub fn select_entries(
+    claim_ref: &ClaimRef,
+    file: &str,
+    config: &ConfigFormat,
+    file_type: &ConfigFileType,
+) -> Result<Vec<(String, String)>, Error> {
+    let explode_leaves = claim_ref.explode.unwrap_or(false);
+    if claim_ref.selector.is_none() && !explode_leaves {
+        return Ok(vec![(
+            file.to_string(),
+            convert_to_format(config, file_type)?,
+        )]);
+    }
+
+    let ConfigFormat::Json(document) = convert_to_json(config)? else {
+        return Err(Error::UnsupportedFileType());
+    };
+    let selected = select(&document, claim_ref.selector.as_deref().unwrap_or(""))?;
+    if explode_leaves && (selected.is_object() || selected.is_array()) {
+        return explode(selected)
+            .into_iter()
+            .map(|(leaf, content)| {
+                let key = format!("{}.{}", file, leaf);
+                if is_valid_data_key(&key) {
+                    Ok((key, content))
+                } else {
+                    Err(Error::InvalidDataKey(key))
+                }
+            })
+            .collect();
+    }
+    let content = match scalar_to_string(selected) {
+        Some(value) => value,
+        None => convert_to_format(&ConfigFormat::Json(selected.clone()), file_type)?,
+    };
+    Ok(vec![(file.to_string(), content)])
+}
+
+/// Whether missing target is created. Merge and None only write into existing target.
+pub fn creates_target(policy: &ClaimCreationPolicy) -> bool {
+    matches!(
+        policy,
+        ClaimCreationPolicy::Owned | ClaimCreationPolicy::Orphan
+    )
+}
+
+/// Whether `key` may be used as ConfigMap or Secret data key.
+pub fn is_valid_data_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= 253
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_'))
+}
+
+/// Key of `file_data` already written by another data key of the claim.
+pub fn duplicate_key(data: &ComposedData, file_data: &ComposedData) -> Option<String> {
+    file_data
+        .data
+        .keys()
+        .chain(file_data.binary_data.keys())
+        .find(|key| data.data.contains_key(*key) || data.binary_data.contains_key(*key))
+        .cloned()
+}
+
+/// Content of data key hashed into its status. Exploded key is represented by all its entries.
+pub fn composed_content(file: &str, entries: &ComposedData) -> Vec<u8> {
+    if let Some(content) = entries.binary_data.get(file) {
+        return content.clone();
+    }
+    match entries.data.get(file) {
+        Some(content) if entries.data.len() == 1 => content.clone().into_bytes(),
+        _ => entries
+            .data
+            .iter()
+            .map(|(key, value)| format!("{}={}\n", key, value))
+            .collect::<String>()
+            .into_bytes(),
+    }
+}
+
+/// Names of previous targets to delete, keeping `history_limit` newest ones besides current.
+pub fn targets_to_prune(
+    current: &str,
+    mut targets: Vec<(String, Option<Time>)>,
+    history_limit: usize,
+) -> Vec<String> {
+    targets.retain(|(name, _)| name != current);
+    targets.sort_by(|(_, a), (_, b)| b.cmp(a));
+    targets
+        .into_iter()
+        .skip(history_limit)
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Whether target already holds desired data. Target may contain keys managed by others,
+/// so only claimed keys are compared.
+pub fn target_data_synced<V: PartialEq>(
+    existing: Option<&BTreeMap<String, V>>,
+    desired: Option<&BTreeMap<String, V>>,
+) -> bool {
+    desired
+        .into_iter()
+        .flatten()
+        .all(|(key, value)| existing.and_then(|existing| existing.get(key)) == Some(value))
+}
+
+/// Field manager targets were applied with before each claim got its own one.
+pub static LEGACY_FIELD_MANAGER: &str = "configmap-claim-controller";
+
+/// Whether field manager owns any field of the object.
+pub fn managed_by(meta: &ObjectMeta, manager: &str) -> bool {
+    meta.managed_fields
+        .iter()
+        .flatten()
+        .any(|entry| entry.manager.as_deref() == Some(manager))
+}
+
+/// Field manager names are limited to 128 characters by API server.
+pub fn field_manager(kind: &str, name: &str) -> String {
+    format!("external-config/{}/{}", kind, name)
+        .chars()
+        .take(128)
+        .collect()
+}
+
+fn map_apply_error(error: kube::Error) -> Error {
+    match error {
+        kube::Error::Api(ErrorResponse {
+            code: 409, message, ..
+        }) => Error::TargetApplyConflict(message),
+        e => Error::KubeError(e),
+    }
 }
@@ -0,0 +1,364 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::lib::Error;
+    use crate::controller::utils::file_format::{ConfigFileType, ConfigFormat};
+    use crate::controller::v1alpha1::configuration_discoverer::{
+        content_hash, creates_target, decode_base64_payload, duplicate_key, field_manager,
+        immutable_target_name, is_valid_data_key, managed_by, output_file_type, parsed_content_key,
+        select_entries, stores_failed, target_data_synced, targets_to_prune, ComposedData,
+        LEGACY_FIELD_MANAGER,
+    };
+    use crate::controller::v1alpha1::crd::claim::{
+        ClaimCreationPolicy, ClaimRef, ClaimRefParametrization,
+    };
+    use chrono::{TimeDelta, Utc};
+    use k8s_openapi::api::core::v1::{ConfigMap, Secret};
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ObjectMeta, Time};
+    use k8s_openapi::ByteString;
+    use std::collections::BTreeMap;
+
+    fn data(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_keys_not_claimed_are_ignored() {
+        let desired = data(&[("app.json", "{}")]);
+        let with_foreign_key = data(&[("app.json", "{}"), ("other", "value")]);
+        let outdated = data(&[("app.json", "{\"old\": true}"), ("other", "value")]);
+
+        assert!(target_data_synced(Some(&desired), Some(&desired)));
+        assert!(target_data_synced(Some(&with_foreign_key), Some(&desired)));
+        assert!(!target_data_synced(Some(&outdated), Some(&desired)));
+        assert!(!target_data_synced(None, Some(&desired)));
+    }
+
+    #[test]
+    fn test_field_manager_is_unique_per_claim_and_limited_in_length() {
+        assert_eq!(
+            field_manager("ConfigMapClaim", "app"),
+            "external-config/ConfigMapClaim/app"
+        );
+        assert_ne!(
+            field_manager("ConfigMapClaim", "app"),
+            field_manager("SecretClaim", "app")
+        );
+        assert_eq!(field_manager("SecretClaim", &"a".repeat(253)).len(), 128);
+    }
+
+    #[test]
+    fn test_targets_applied_by_legacy_field_manager_are_taken_over() {
+        let meta: ObjectMeta = serde_json::from_value(serde_json::json!({
+            "name": "app-config",
+            "managedFields": [
+                { "manager": "kubectl", "operation": "Update" },
+                { "manager": LEGACY_FIELD_MANAGER, "operation": "Apply" }
+            ]
+        }))
+        .unwrap();
+
+        assert!(managed_by(&meta, LEGACY_FIELD_MANAGER));
+        assert!(!managed_by(&meta, &field_manager("ConfigMapClaim", "app")));
+        assert!(!managed_by(&ObjectMeta::default(), LEGACY_FIELD_MANAGER));
+    }
+
+    fn composed(entries: &[(&str, &str)]) -> ComposedData {
+        ComposedData {
+            data: data(entries),
+            ..Default::default()
+        }
+    }
+
+    fn config_map(entries: &[(&str, &str)]) -> ConfigMap {
+        ConfigMap {
+            data: Some(data(entries)),
+            ..Default::default()
+        }
+    }
+
+    fn immutable_name(target: &impl serde::Serialize) -> String {
+        immutable_target_name("app-config", &content_hash(target))
+    }
+
+    #[test]
+    fn test_immutable_target_name_follows_content() {
+        let name = immutable_name(&config_map(&[("app.json", "{}")]));
+
+        assert!(name.starts_with("app-config-"));
+        assert_eq!(name.len(), "app-config-".len() + 10);
+        assert_eq!(name, immutable_name(&config_map(&[("app.json", "{}")])));
+        assert_ne!(
+            name,
+            immutable_name(&config_map(&[("app.json", "{\"a\": 1}")]))
+        );
+        // Key and value boundaries are part of the digest
+        assert_ne!(
+            immutable_name(&config_map(&[("ab", "c")])),
+            immutable_name(&config_map(&[("a", "bc")]))
+        );
+        // Metadata, e.g. name the hash ends up in, is not
+        let mut named = config_map(&[("app.json", "{}")]);
+        named.metadata.name = Some(name.clone());
+        assert_eq!(name, immutable_name(&named));
+    }
+
+    #[test]
+    fn test_secret_type_and_key_mapping_are_part_of_immutable_target_name() {
+        let secret = |type_: &str, key: &str| Secret {
+            data: Some([(key.to_string(), ByteString(b"admin".to_vec()))].into()),
+            type_: Some(type_.to_string()),
+            ..Default::default()
+        };
+
+        assert_ne!(
+            immutable_name(&secret("Opaque", "username")),
+            immutable_name(&secret("kubernetes.io/basic-auth", "username"))
+        );
+        assert_ne!(
+            immutable_name(&secret("Opaque", "username")),
+            immutable_name(&secret("Opaque", "user"))
+        );
+    }
+
+    #[test]
+    fn test_oldest_targets_beyond_history_limit_are_pruned() {
+        let created = |minutes_ago: i64| Some(Time(Utc::now() - TimeDelta::minutes(minutes_ago)));
+        let targets = vec![
+            (String::from("app-oldest"), created(30)),
+            (String::from("app-current"), created(0)),
+            (String::from("app-previous"), created(10)),
+            (String::from("app-older"), created(20)),
+        ];
+
+        assert_eq!(
+            targets_to_prune("app-current", targets.clone(), 2),
+            vec![String::from("app-oldest")]
+        );
+        assert_eq!(
+            targets_to_prune("app-current", targets.clone(), 0),
+            vec![
+                String::from("app-previous"),
+                String::from("app-older"),
+                String::from("app-oldest")
+            ]
+        );
+        assert!(targets_to_prune("app-current", targets, 5).is_empty());
+    }
+
+    fn selecting(selector: Option<&str>, explode: bool) -> ClaimRef {
+        ClaimRef {
+            from: vec![],
+            strategy: None,
+            selector: selector.map(String::from),
+            explode: Some(explode),
+            template: None,
+            output_format: None,
+            format: None,
+            base64_decode: None,
+            merge_options: None,
+        }
+    }
+
+    #[test]
+    fn test_selector_writes_scalar_or_formatted_sub_tree() {
+        let config = ConfigFormat::Json(serde_json::json!({
+            "database": { "password": "s3cret", "port": 5432 }
+        }));
+
+        let entries = select_entries(
+            &selecting(Some("/database/password"), false),
+            "password",
+            &config,
+            &ConfigFileType::Yaml,
+        )
+        .unwrap();
+        assert_eq!(
+            entries,
+            vec![(String::from("password"), String::from("s3cret"))]
+        );
+
+        let entries = select_entries(
+            &selecting(Some("$.database"), false),
+            "database.yaml",
+            &config,
+            &ConfigFileType::Yaml,
+        )
+        .unwrap();
+        assert_eq!(entries[0].0, "database.yaml");
+        assert!(entries[0].1.contains("port: 5432"));
+    }
+
+    #[test]
+    fn test_exploded_selection_writes_key_per_leaf() {
+        let config = ConfigFormat::Json(serde_json::json!({
+            "database": { "password": "s3cret", "pool": { "size": 5 } }
+        }));
+
+        let entries = select_entries(
+            &selecting(Some("/database"), true),
+            "database",
+            &config,
+            &ConfigFileType::Json,
+        )
+        .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                (String::from("database.password"), String::from("s3cret")),
+                (String::from("database.pool.size"), String::from("5")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exploded_leaf_with_invalid_key_is_rejected() {
+        let config = ConfigFormat::Json(serde_json::json!({
+            "database": { "pass word": "s3cret" }
+        }));
+
+        let result = select_entries(
+            &selecting(Some("/database"), true),
+            "database",
+            &config,
+            &ConfigFileType::Json,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::InvalidDataKey(key)) if key == "database.pass word"
+        ));
+        assert!(is_valid_data_key("database.pool-1_size"));
+        assert!(!is_valid_data_key("database/password"));
+        assert!(!is_valid_data_key(""));
+    }
+
+    #[test]
+    fn test_key_written_by_two_data_keys_is_detected() {
+        let composed = |entries: &[(&str, &str)]| ComposedData {
+            data: data(entries),
+            ..Default::default()
+        };
+        let written = composed(&[("database.password", "s3cret")]);
+
+        assert_eq!(
+            duplicate_key(&written, &composed(&[("database.password", "other")])),
+            Some(String::from("database.password"))
+        );
+        assert_eq!(
+            duplicate_key(&written, &composed(&[("database.host", "db")])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_raw_payload_is_part_of_immutable_target_name() {
+        let with_keystore = |keystore: &[u8]| ConfigMap {
+            binary_data: Some(
+                [(String::from("keystore.p12"), ByteString(keystore.to_vec()))].into(),
+            ),
+            ..config_map(&[("app.json", "{}")])
+        };
+
+        assert_ne!(
+            immutable_name(&with_keystore(&[0x30, 0x82])),
+            immutable_name(&with_keystore(&[0x30, 0x83]))
+        );
+        assert_ne!(
+            immutable_name(&with_keystore(&[0x30, 0x82])),
+            immutable_name(&config_map(&[("app.json", "{}")]))
+        );
+    }
+
+    #[test]
+    fn test_base64_payload_is_decoded_ignoring_line_breaks() {
+        assert_eq!(
+            decode_base64_payload(b"AAEC\n//8=\n").unwrap(),
+            vec![0x00, 0x01, 0x02, 0xff, 0xff]
+        );
+        assert!(decode_base64_payload(b"not base64!").is_err());
+    }
+
+    #[test]
+    fn test_output_format_overrides_file_extension() {
+        let mut claim_ref = selecting(None, false);
+
+        assert!(matches!(
+            output_file_type(&claim_ref, "app.yaml"),
+            Some(ConfigFileType::Yaml)
+        ));
+        assert!(output_file_type(&claim_ref, "app").is_none());
+
+        claim_ref.output_format = Some(ConfigFileType::EnvFile);
+        assert!(matches!(
+            output_file_type(&claim_ref, "app.yaml"),
+            Some(ConfigFileType::EnvFile)
+        ));
+    }
+
+    #[test]
+    fn test_failure_of_every_store_is_reported() {
+        let error = stores_failed(vec![
+            (
+                String::from("ConfigurationStore/primary"),
+                Error::HttpConfigStoreServerError(std::io::Error::other("unavailable")),
+            ),
+            (
+                String::from("ClusterConfigurationStore/backup"),
+                Error::UnsupportedFileType(),
+            ),
+        ]);
+
+        let message = error.to_string();
+        assert!(
+            message.contains("ConfigurationStore/primary: ") && message.contains("unavailable"),
+            "{}",
+            message
+        );
+        assert!(
+            message.contains("ClusterConfigurationStore/backup: Unsupported"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_parsed_content_is_keyed_by_store_instance() {
+        let store_ref: ClaimRefParametrization = serde_json::from_value(serde_json::json!({
+            "configurationStoreRef": { "name": "store", "kind": "ConfigurationStore" },
+            "configurationStoreParams": { "env": "prod" }
+        }))
+        .unwrap();
+        let store = |uid: &str, generation: i64| ObjectMeta {
+            name: Some(String::from("store")),
+            uid: Some(uid.to_string()),
+            generation: Some(generation),
+            ..Default::default()
+        };
+        let key = parsed_content_key(&store("uid", 1), &store_ref, "default");
+
+        assert_eq!(
+            key,
+            parsed_content_key(&store("uid", 1), &store_ref, "default")
+        );
+        // Spec changed or store was recreated under the same name
+        assert_ne!(
+            key,
+            parsed_content_key(&store("uid", 2), &store_ref, "default")
+        );
+        assert_ne!(
+            key,
+            parsed_content_key(&store("recreated", 1), &store_ref, "default")
+        );
+    }
+
+    #[test]
+    fn test_only_owned_and_orphan_targets_are_created() {
+        assert!(creates_target(&ClaimCreationPolicy::Owned));
+        assert!(creates_target(&ClaimCreationPolicy::Orphan));
+        // Merge writes into target the claim does not own, it has to exist
+        assert!(!creates_target(&ClaimCreationPolicy::Merge));
+        assert!(!creates_target(&ClaimCreationPolicy::None));
+    }
+}
@@ -1,6 +1,8 @@
 use crate::controller::controller::run as startController;
 use crate::controller::utils::context::Context;
+use crate::controller::v1alpha1::store_controller;
 use futures::join;
+use k8s_openapi::api::core::v1::{ConfigMap, Secret};
 use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
 use kube::CustomResourceExt;
 
@@ -11,8 +13,10 @@ pub use crate::controller::v1alpha1::crd::configuration_store::{
 
 pub async fn run(data: Context) {
     join![
-        startController::<ConfigMapClaim>(data.clone()),
-        startController::<SecretClaim>(data.clone())
+        startController::<ConfigMapClaim, ConfigMap>(data.clone()),
+        startController::<SecretClaim, Secret>(data.clone()),
+        store_controller::run::<ConfigurationStore>(data.clone()),
+        store_controller::run::<ClusterConfigurationStore>(data.clone())
     ];
 }
 
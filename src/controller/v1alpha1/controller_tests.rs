@@ -2,11 +2,11 @@
 mod tests {
     use crate::contract::clients::K8sClientAware;
     use crate::contract::lib::Error;
+    use crate::controller::utils::config::Config as AppConfig;
     use crate::controller::v1alpha1::crd::claim::{
         ClaimConfigurationStoreRef, SupportedConfigurationStoreResourceType,
     };
     use crate::controller::v1alpha1::crd::configuration_store::ClusterConfigurationStore;
-    use crate::controller::utils::config::Config as AppConfig;
 
     use colored::*;
     use futures::future::join_all;
@@ -23,7 +23,6 @@ mod tests {
     #[cfg(not(test))]
     use log::{info, warn};
 
-    use base64::Engine;
     use kube::api::{ListParams, ObjectList};
     use kube::runtime::Controller;
     use std::ffi::c_double;
@@ -33,17 +32,28 @@ mod tests {
 
     use crate::contract::clients::ICrdClient;
     use crate::contract::clients::K8sClient;
+    use crate::contract::iconfigstore::IConfigStore;
     use crate::contract::ireconcilable::IReconcilable;
+    use crate::controller::config_store::content_cache::ParsedContentCache;
+    use crate::controller::config_store::store_registry::ConfigStoreRegistry;
     use crate::controller::controller::{apply_all_crds, apply_from_yaml, reconcile};
     use crate::controller::utils::context::Context;
+    use crate::controller::v1alpha1::claim_triggers::{ResyncRequests, StoreChanges};
     use crate::controller::v1alpha1::controller::{ConfigMapClaim, ConfigurationStore};
+    use crate::controller::v1alpha1::crd::claim::HasClaimData;
     use crate::controller::v1alpha1::crd_client::CrdClient;
     use crate::controller::v1alpha1::fixtures::tests::{ControllerFixtures, MockConfig};
+    use crate::controller::v1alpha1::rollout::rollout_annotation;
     use crate::observability::metrics::Metrics;
-    use base64::engine::general_purpose::STANDARD;
+    use k8s_openapi::api::apps::v1::Deployment;
     use k8s_openapi::ByteString;
+    use kube::api::{Patch, PatchParams};
+    use kube::runtime::reflector::ObjectRef;
     use log::error;
+    use serde_json::json;
     use tokio::task::JoinError;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     // Global array of Kubernetes versions
     const K8S_VERSIONS: &[&str] = &[
@@ -144,6 +154,10 @@ mod tests {
                                 v1alpha1: crd_client.clone(),
                                 api_client: crd_client.clone(),
                                 metrics: Arc::new(Metrics::default()),
+                                config_stores: Arc::new(ConfigStoreRegistry::default()),
+                                parsed_content: Arc::new(ParsedContentCache::default()),
+                                claim_resync: Arc::new(ResyncRequests::default()),
+                                store_changes: Arc::new(StoreChanges::default()),
                             });
                             let cloned_client = client.clone();
                             tasks.push(tokio::task::spawn(async move {
@@ -215,7 +229,7 @@ mod tests {
             .get_config_store(format!("{}-store", store_name).as_str(), namespace)
             .await?;
 
-        let config_store = store.spec.provider.get_config_store();
+        let config_store = store.get_config_store(&ctx).await?;
 
         let config = config_store
             .get_config(None, None)
@@ -253,7 +267,7 @@ mod tests {
             .get_config_store(format!("{}-store", store_name).as_str(), namespace)
             .await?;
 
-        let config_store = store.spec.provider.get_config_store();
+        let config_store = store.get_config_store(&ctx).await?;
 
         let config = config_store
             .get_config(None, None)
@@ -285,7 +299,7 @@ mod tests {
             .get_cluster_config_store(format!("{}-store", store_name).as_str())
             .await?;
 
-        let config_store = store.spec.provider.get_config_store();
+        let config_store = store.get_config_store(&ctx).await?;
 
         let config = config_store
             .get_config(None, None)
@@ -320,7 +334,7 @@ mod tests {
             .get_config_store(format!("{}-store", store_name).as_str(), namespace)
             .await?;
 
-        let config_store = store.spec.provider.get_config_store();
+        let config_store = store.get_config_store(&ctx).await?;
 
         let config = config_store.get_config(None, None).await;
 
@@ -357,7 +371,7 @@ mod tests {
             .get_config_store(format!("{}-store", store_name).as_str(), namespace)
             .await?;
 
-        let config_store = store.spec.provider.get_config_store();
+        let config_store = store.get_config_store(&ctx).await?;
 
         let config = config_store.get_config(None, None).await;
 
@@ -395,7 +409,7 @@ mod tests {
             .get_config_store(format!("{}-store", store_name).as_str(), namespace)
             .await?;
 
-        let config_store = store.spec.provider.get_config_store();
+        let config_store = store.get_config_store(&ctx).await?;
 
         let mut params1: HashMap<String, String> = HashMap::new();
         params1.insert(String::from("test1"), String::from("value1"));
@@ -475,11 +489,11 @@ mod tests {
         let str3 = sm_data.get("config.properties").unwrap();
         let str4 = sm_data.get("config.env").unwrap();
 
-        let sdata = String::from_utf8(STANDARD.decode(&str.0).unwrap()).unwrap();
-        let sdata1 = String::from_utf8(STANDARD.decode(&str1.0).unwrap()).unwrap();
-        let sdata2 = String::from_utf8(STANDARD.decode(&str2.0).unwrap()).unwrap();
-        let sdata3 = String::from_utf8(STANDARD.decode(&str3.0).unwrap()).unwrap();
-        let sdata4 = String::from_utf8(STANDARD.decode(&str4.0).unwrap()).unwrap();
+        let sdata = String::from_utf8(str.0.clone()).unwrap();
+        let sdata1 = String::from_utf8(str1.0.clone()).unwrap();
+        let sdata2 = String::from_utf8(str2.0.clone()).unwrap();
+        let sdata3 = String::from_utf8(str3.0.clone()).unwrap();
+        let sdata4 = String::from_utf8(str4.0.clone()).unwrap();
 
         assert_eq!(data, sdata);
         assert_eq!(data1, sdata1);
@@ -585,11 +599,11 @@ DB__CONFIG_LOG__LEVELS_1=DEBUG"#
         let str3 = sm_data.get("config.properties").unwrap();
         let str4 = sm_data.get("config.env").unwrap();
 
-        let sdata = String::from_utf8(STANDARD.decode(&str.0).unwrap()).unwrap();
-        let sdata1 = String::from_utf8(STANDARD.decode(&str1.0).unwrap()).unwrap();
-        let sdata2 = String::from_utf8(STANDARD.decode(&str2.0).unwrap()).unwrap();
-        let sdata3 = String::from_utf8(STANDARD.decode(&str3.0).unwrap()).unwrap();
-        let sdata4 = String::from_utf8(STANDARD.decode(&str4.0).unwrap()).unwrap();
+        let sdata = String::from_utf8(str.0.clone()).unwrap();
+        let sdata1 = String::from_utf8(str1.0.clone()).unwrap();
+        let sdata2 = String::from_utf8(str2.0.clone()).unwrap();
+        let sdata3 = String::from_utf8(str3.0.clone()).unwrap();
+        let sdata4 = String::from_utf8(str4.0.clone()).unwrap();
 
         assert_eq!(data, sdata);
         assert_eq!(data1, sdata1);
@@ -704,11 +718,11 @@ DB__CONFIG_TIMEOUT=1000"#
         let str3 = sm_data.get("config.properties").unwrap();
         let str4 = sm_data.get("config.env").unwrap();
 
-        let sdata = String::from_utf8(STANDARD.decode(&str.0).unwrap()).unwrap();
-        let sdata1 = String::from_utf8(STANDARD.decode(&str1.0).unwrap()).unwrap();
-        let sdata2 = String::from_utf8(STANDARD.decode(&str2.0).unwrap()).unwrap();
-        let sdata3 = String::from_utf8(STANDARD.decode(&str3.0).unwrap()).unwrap();
-        let sdata4 = String::from_utf8(STANDARD.decode(&str4.0).unwrap()).unwrap();
+        let sdata = String::from_utf8(str.0.clone()).unwrap();
+        let sdata1 = String::from_utf8(str1.0.clone()).unwrap();
+        let sdata2 = String::from_utf8(str2.0.clone()).unwrap();
+        let sdata3 = String::from_utf8(str3.0.clone()).unwrap();
+        let sdata4 = String::from_utf8(str4.0.clone()).unwrap();
 
         assert_eq!(data, sdata);
         assert_eq!(data1, sdata1);
@@ -814,11 +828,11 @@ DB__CONFIG_LOG__LEVELS_1=DEBUG"#
         let str3 = sm_data.get("config.properties").unwrap();
         let str4 = sm_data.get("config.env").unwrap();
 
-        let sdata = String::from_utf8(STANDARD.decode(&str.0).unwrap()).unwrap();
-        let sdata1 = String::from_utf8(STANDARD.decode(&str1.0).unwrap()).unwrap();
-        let sdata2 = String::from_utf8(STANDARD.decode(&str2.0).unwrap()).unwrap();
-        let sdata3 = String::from_utf8(STANDARD.decode(&str3.0).unwrap()).unwrap();
-        let sdata4 = String::from_utf8(STANDARD.decode(&str4.0).unwrap()).unwrap();
+        let sdata = String::from_utf8(str.0.clone()).unwrap();
+        let sdata1 = String::from_utf8(str1.0.clone()).unwrap();
+        let sdata2 = String::from_utf8(str2.0.clone()).unwrap();
+        let sdata3 = String::from_utf8(str3.0.clone()).unwrap();
+        let sdata4 = String::from_utf8(str4.0.clone()).unwrap();
 
         assert_eq!(data, sdata);
         assert_eq!(data1, sdata1);
@@ -874,6 +888,240 @@ DB__CONFIG_TIMEOUT=1000"#
         );
         Ok(String::from("Done"))
     }
+    async fn test_http_store_credentials_resolved_from_secrets(
+        ctx: Arc<Context>,
+        fixture: &mut ControllerFixtures,
+    ) -> Result<String, Error> {
+        let store_name = "test-http-store-credentials-from-secrets";
+        let namespace = "default";
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/config"))
+            .and(header("Authorization", "Bearer token-from-secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"asd\": 1}"))
+            .mount(&mock_server)
+            .await;
+
+        apply_from_yaml(
+            ctx.client.clone(),
+            &format!(
+                r#"
+apiVersion: v1
+kind: Secret
+metadata:
+  name: {store_name}-credentials
+  namespace: {namespace}
+stringData:
+  token: token-from-secret
+"#
+            ),
+        )
+        .await
+        .unwrap();
+
+        for (kind, secret_namespace) in [
+            ("ConfigurationStore", None),
+            ("ClusterConfigurationStore", None),
+            ("ClusterConfigurationStore", Some(namespace)),
+        ] {
+            let name = match secret_namespace {
+                Some(ns) => format!("{}-{}-{}", store_name, kind.to_lowercase(), ns),
+                None => format!("{}-{}", store_name, kind.to_lowercase()),
+            };
+            let secret_namespace = secret_namespace
+                .map(|ns| format!("namespace: {}", ns))
+                .unwrap_or_default();
+            apply_from_yaml(
+                ctx.client.clone(),
+                &format!(
+                    r#"
+apiVersion: external-config.com/v1alpha1
+kind: {kind}
+metadata:
+  name: {name}
+  namespace: {namespace}
+spec:
+  provider:
+    http:
+      baseUrl: {address}/config
+      auth:
+        bearer:
+          secretRef:
+            name: {store_name}-credentials
+            {secret_namespace}
+            key: token
+"#,
+                    address = mock_server.address()
+                ),
+            )
+            .await
+            .unwrap();
+        }
+
+        let store = ctx
+            .v1alpha1
+            .get_config_store(
+                format!("{}-configurationstore", store_name).as_str(),
+                namespace,
+            )
+            .await?;
+        let config = store
+            .get_config_store(&ctx)
+            .await?
+            .get_config(None, None)
+            .await?;
+        assert_eq!(config, "{\"asd\": 1}");
+
+        let cluster_store = ctx
+            .v1alpha1
+            .get_cluster_config_store(format!("{}-clusterconfigurationstore", store_name).as_str())
+            .await?;
+        match cluster_store.get_config_store(&ctx).await {
+            Err(Error::SecretReferenceError(_)) => {}
+            _ => panic!("Expected Error::SecretReferenceError for Secret without namespace"),
+        }
+
+        let cluster_store = ctx
+            .v1alpha1
+            .get_cluster_config_store(
+                format!("{}-clusterconfigurationstore-{}", store_name, namespace).as_str(),
+            )
+            .await?;
+        let config = cluster_store
+            .get_config_store(&ctx)
+            .await?
+            .get_config(None, None)
+            .await?;
+        assert_eq!(config, "{\"asd\": 1}");
+
+        Ok(String::from("Done"))
+    }
+
+    async fn test_rollout_only_when_target_content_changes(
+        ctx: Arc<Context>,
+        _fixture: &mut ControllerFixtures,
+    ) -> Result<String, Error> {
+        let claim_name = "test-rollout-on-content-change";
+        let namespace = "default";
+
+        let mock_server = MockServer::start().await;
+        let serve = |body: &'static str| {
+            Mock::given(method("GET"))
+                .and(path("/config"))
+                .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        };
+        serve("{\"asd\": 1}").mount(&mock_server).await;
+
+        let manifests = format!(
+            r#"
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: {claim_name}-app
+  namespace: {namespace}
+spec:
+  selector:
+    matchLabels:
+      app: {claim_name}
+  template:
+    metadata:
+      labels:
+        app: {claim_name}
+    spec:
+      containers:
+        - name: app
+          image: busybox
+---
+apiVersion: external-config.com/v1alpha1
+kind: ConfigurationStore
+metadata:
+  name: {claim_name}-store
+  namespace: {namespace}
+spec:
+  provider:
+    http:
+      baseUrl: {address}/config
+---
+apiVersion: external-config.com/v1alpha1
+kind: ConfigMapClaim
+metadata:
+  name: {claim_name}
+  namespace: {namespace}
+spec:
+  data:
+    config.json:
+      from:
+        - configurationStoreRef:
+            kind: ConfigurationStore
+            name: {claim_name}-store
+  target:
+    name: {claim_name}
+    creationPolicy: Owned
+    rolloutTargets:
+      - kind: Deployment
+        name: {claim_name}-app
+"#,
+            address = mock_server.address()
+        );
+        for manifest in manifests.split("\n---\n") {
+            apply_from_yaml(ctx.client.clone(), manifest).await.unwrap();
+        }
+
+        let deployments: Api<Deployment> = Api::namespaced((*ctx.client).clone(), namespace);
+        let annotation = rollout_annotation("ConfigMapClaim", claim_name);
+        // Syncs claim as resync would, regardless of refresh interval
+        let resync = || async {
+            let claim = ctx
+                .v1alpha1
+                .get_config_map_claim(claim_name, namespace)
+                .await?;
+            ctx.claim_resync.request(&ObjectRef::from_obj(&claim));
+            claim.reconcile(ctx.clone()).await?;
+            let rolled_out = deployments
+                .get(&format!("{}-app", claim_name))
+                .await
+                .map_err(Error::KubeError)?
+                .spec
+                .and_then(|spec| spec.template.metadata)
+                .and_then(|metadata| metadata.annotations)
+                .and_then(|annotations| annotations.get(&annotation).cloned());
+            Ok::<_, Error>(rolled_out)
+        };
+
+        // Enabling rollout only records the hash of current content
+        assert_eq!(resync().await?, None);
+        assert_eq!(resync().await?, None);
+
+        mock_server.reset().await;
+        serve("{\"asd\": 2}").mount(&mock_server).await;
+        let rolled_out = resync().await?;
+        let claim = ctx
+            .v1alpha1
+            .get_config_map_claim(claim_name, namespace)
+            .await?;
+        let rollout_hash = claim
+            .get_claim_status()
+            .and_then(|status| status.rollout_hash.clone());
+        assert!(rolled_out.is_some());
+        assert_eq!(rolled_out, rollout_hash);
+
+        // Unchanged content does not restart workloads again
+        deployments
+            .patch(
+                &format!("{}-app", claim_name),
+                &PatchParams::default(),
+                &Patch::Merge(json!({
+                    "spec": { "template": { "metadata": { "annotations": { &annotation: null } } } }
+                })),
+            )
+            .await
+            .map_err(Error::KubeError)?;
+        assert_eq!(resync().await?, None);
+
+        Ok(String::from("Done"))
+    }
+
     async fn test_other_feature(
         ctx: Arc<Context>,
         fixture: &mut ControllerFixtures,
@@ -915,6 +1163,8 @@ DB__CONFIG_TIMEOUT=1000"#
             test_config_files_with_merging_reconcilation,
             test_config_files_with_merging_reconcilation_with_cluster_config_store,
             test_basic_reconcilation_with_cluster_config_store,
+            test_http_store_credentials_resolved_from_secrets,
+            test_rollout_only_when_target_content_changes,
 
            // test_other_feature,
            // test_other_feature2,
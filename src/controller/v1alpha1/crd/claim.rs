@@ -8,10 +8,9 @@ use crate::controller::v1alpha1::crd::configuration_store::{
     ClusterConfigurationStore, ConfigStoreFetcherAdapter, ConfigurationStore, Provider,
 };
 use async_trait::async_trait;
-use base64::engine::general_purpose::STANDARD;
-use base64::Engine;
 use chrono::format::{parse, ParseErrorKind};
 use k8s_openapi::api::core::v1::{ConfigMap, Secret};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
 use k8s_openapi::{ByteString, NamespaceResourceScope};
 use kube::api::{DeleteParams, Patch, PatchParams, PostParams};
 use kube::core::object::HasSpec;
@@ -24,6 +23,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::json;
 use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::time::Duration;
@@ -31,10 +31,12 @@ use tracing::info;
 
 use crate::controller::utils::file_format::{
     convert_to_format, convert_to_json, merge_configs, to_file_type, to_file_type_from_filename,
-    ConfigFileType, ConfigFormat,
+    ConfigFileType, ConfigFormat, MergeOptions,
 };
 use crate::controller::utils::parsers::text_to_json::try_parse_file_to_json;
-use crate::controller::v1alpha1::configuration_discoverer::ConfigurationDiscoverer;
+use crate::controller::v1alpha1::configuration_discoverer::{
+    ComposedData, ConfigurationDiscoverer,
+};
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 enum SupportedClaimResourceType {
@@ -47,16 +49,35 @@ pub enum ConfigInjectionStrategy {
     Merge,
     Fallback,
 }
+
+/// How fetched payload is written to data key.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ClaimDataFormat {
+    /// Parsed as configuration document, which can be merged, selected from and templated.
+    Structured,
+    /// Passed through untouched, e.g. certificates, keystores or plain text. Written to
+    /// `binaryData` of ConfigMap. First store serving it is used, as with `Fallback` strategy.
+    Raw,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub enum SupportedConfigurationStoreResourceType {
     ConfigurationStore,
     ClusterConfigurationStore,
 }
-#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+/// How the claim treats its target ConfigMap/Secret
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
 pub enum ClaimCreationPolicy {
+    /// Target is created and owned by the claim, and garbage collected with it
     Owned,
+    /// Target is created without owner reference and left behind when claim is deleted
     Orphan,
+    /// Only claimed keys of existing target are managed, other keys are left untouched.
+    /// Claim reports error when target is missing, claimed keys are released when claim
+    /// is deleted
     Merge,
+    /// Only existing target is updated, claim reports error when it is missing
     None,
 }
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
@@ -65,21 +86,142 @@ pub struct ClaimConfigurationStoreRef {
     pub kind: SupportedConfigurationStoreResourceType,
 }
 
+impl Display for ClaimConfigurationStoreRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}/{}", self.kind, self.name)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct ClaimTargetRef {
     pub name: String,
     pub creationPolicy: ClaimCreationPolicy,
+    /// Take over claimed keys managed by other field managers instead of reporting conflict.
+    /// Defaults to `false`
+    #[serde(rename = "forceConflicts")]
+    pub force_conflicts: Option<bool>,
+    pub template: Option<ClaimTargetTemplate>,
+    /// Create target as immutable object named after its content, e.g. `app-config-3f2a9c0d1e`.
+    /// Changed content creates new object, name of current one is reported in `status.targetName`
+    pub immutable: Option<bool>,
+    /// Number of previous immutable targets kept after content changed. Defaults to `2`
+    #[serde(rename = "historyLimit")]
+    pub history_limit: Option<u32>,
+    /// Workloads restarted when content of the target changes, by annotating their pod
+    /// template with content hash. Enabling rollout does not restart them.
+    #[serde(rename = "rolloutTargets")]
+    pub rollout_targets: Option<Vec<RolloutTarget>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub enum RolloutTargetKind {
+    Deployment,
+    StatefulSet,
+    DaemonSet,
+}
+
+/// Workload in namespace of the claim, picked by name or by label selector, e.g. `app=api`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct RolloutTarget {
+    pub kind: RolloutTargetKind,
+    pub name: Option<String>,
+    pub selector: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SecretClaimTargetRef {
+    #[serde(flatten)]
+    pub common: ClaimTargetRef,
+    /// Type of generated Secret. Defaults to `Opaque`
+    #[serde(rename = "type")]
+    pub secret_type: Option<SecretType>,
+    /// Renames claim data keys to keys required by Secret type, e.g. `tls.crt: certificate.pem`
+    #[serde(rename = "keyMapping")]
+    pub key_mapping: Option<BTreeMap<String, String>>,
+}
+
+/// Secret types the operator can build. Required keys are validated before Secret is written:
+/// `tls.crt` and `tls.key` for TLS, `.dockerconfigjson` (or `registry`, `username` and `password`
+/// to build it from) for docker config, `username` or `password` for basic auth
+/// and `ssh-privatekey` for SSH auth.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub enum SecretType {
+    Opaque,
+    #[serde(rename = "kubernetes.io/tls")]
+    Tls,
+    #[serde(rename = "kubernetes.io/dockerconfigjson")]
+    DockerConfigJson,
+    #[serde(rename = "kubernetes.io/basic-auth")]
+    BasicAuth,
+    #[serde(rename = "kubernetes.io/ssh-auth")]
+    SshAuth,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct ClaimTargetTemplate {
+    pub metadata: Option<ClaimTargetTemplateMetadata>,
+}
+
+/// Metadata set on target, e.g. annotations triggering Reloader or excluding target from Argo CD sync
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct ClaimTargetTemplateMetadata {
+    pub labels: Option<BTreeMap<String, String>>,
+    pub annotations: Option<BTreeMap<String, String>>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct ClaimRefParametrization {
     pub configurationStoreRef: ClaimConfigurationStoreRef,
     pub configurationStoreParams: Option<HashMap<String, String>>,
+    /// Format the store serves. When not set, `Content-Type` of the response is used as hint,
+    /// falling back to trying every supported format
+    #[serde(rename = "inputFormat")]
+    pub input_format: Option<ConfigFileType>,
 }
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct ClaimRef {
+    #[schemars(length(min = 1))]
     pub from: Vec<ClaimRefParametrization>,
     pub strategy: Option<ConfigInjectionStrategy>,
+    /// Part of fetched document written to data key, as JSON Pointer (`/database/password`)
+    /// or JSONPath subset (`$.database.password`). Scalars are written as plain values,
+    /// sub-trees in format of the data key.
+    #[schemars(regex(pattern = r"^(/|\$)"))]
+    pub selector: Option<String>,
+    /// Writes every leaf under selected value to its own data key, named by this data key
+    /// and the leaf path relative to selected value (`database.host`,
+    /// `database.credentials.password`), instead of this data key. Keys written by more
+    /// than one data key fail the claim.
+    pub explode: Option<bool>,
+    /// Renders data key from Tera template, e.g. nginx.conf or JDBC URL, instead of writing
+    /// the document. Document composed from stores, narrowed by selector, is template context.
+    pub template: Option<ClaimTemplate>,
+    /// Format data key is written in, instead of one inferred from its extension
+    #[serde(rename = "outputFormat")]
+    pub output_format: Option<ConfigFileType>,
+    /// Defaults to `structured`.
+    pub format: Option<ClaimDataFormat>,
+    /// Decodes base64 encoded store response of `raw` data key.
+    #[serde(rename = "base64Decode")]
+    pub base64_decode: Option<bool>,
+    /// Deep merge semantics of `Merge` strategy: array handling, `null` deletion,
+    /// precedence and JSON Patch applied to merged document.
+    #[serde(rename = "mergeOptions")]
+    pub merge_options: Option<MergeOptions>,
+}
+
+/// Template source, either inline or from ConfigMap in claim namespace.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimTemplate {
+    pub inline: Option<String>,
+    pub config_map_ref: Option<ClaimTemplateConfigMapRef>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ClaimTemplateConfigMapRef {
+    pub name: String,
+    pub key: String,
 }
 
 #[derive(CustomResource, Debug, Clone, Deserialize, Serialize, JsonSchema)]
@@ -88,9 +230,16 @@ pub struct ClaimRef {
     version = "v1alpha1",
     kind = "ConfigMapClaim",
     namespaced,
-    shortname = "cmc"
+    shortname = "cmc",
+    category = "externalconfig"
+)]
+#[kube(status = "ClaimStatus")]
+#[kube(
+    printcolumn = r#"{"name":"Ready","type":"string","jsonPath":".status.conditions[?(@.type==\"Ready\")].status"}"#,
+    printcolumn = r#"{"name":"Target","type":"string","jsonPath":".spec.target.name"}"#,
+    printcolumn = r#"{"name":"Last Synced","type":"date","jsonPath":".status.lastSynced"}"#,
+    printcolumn = r#"{"name":"Age","type":"date","jsonPath":".metadata.creationTimestamp"}"#
 )]
-#[kube(status = "ConfigurationSourceStatus")]
 pub struct ConfigMapClaimSpec {
     pub data: HashMap<String, ClaimRef>,
     pub target: ClaimTargetRef,
@@ -102,6 +251,13 @@ pub trait HasTarget {
 pub trait Refreshable {
     fn get_refresh_interval(&self) -> Duration;
 }
+pub trait HasClaimData {
+    fn get_claim_data(&self) -> &HashMap<String, ClaimRef>;
+    fn get_claim_status(&self) -> Option<&ClaimStatus>;
+    fn claim_api(&self, client: Client) -> Api<Self>
+    where
+        Self: Sized;
+}
 
 impl Refreshable for ConfigMapClaim {
     fn get_refresh_interval(&self) -> Duration {
@@ -118,19 +274,34 @@ impl HasTarget for ConfigMapClaim {
     }
 }
 
+impl HasClaimData for ConfigMapClaim {
+    fn get_claim_data(&self) -> &HashMap<String, ClaimRef> {
+        &self.spec.data
+    }
+    fn get_claim_status(&self) -> Option<&ClaimStatus> {
+        self.status.as_ref()
+    }
+    fn claim_api(&self, client: Client) -> Api<Self> {
+        Api::namespaced(
+            client,
+            &<Self as kube::ResourceExt>::namespace(self).unwrap(),
+        )
+    }
+}
+
 #[async_trait]
 impl ConfigurationDiscoverer<ConfigMap> for ConfigMapClaim {
     async fn create_resource_spec(
         &self,
         ctx: Arc<Context>,
+        data: ComposedData,
     ) -> std::result::Result<ConfigMap, Error> {
-        let name = self.spec.target.name.clone();
-        let namespace = <Self as kube::ResourceExt>::namespace(self).unwrap();
-        let mut data: BTreeMap<String, String> = BTreeMap::new();
-
-        for (file, refs) in &self.spec.data {
-            Self::compose_file(self, ctx.clone(), &refs, &namespace, file, &mut data).await?
-        }
+        let metadata = self.target_metadata();
+        let binary_data: BTreeMap<String, ByteString> = data
+            .binary_data
+            .into_iter()
+            .map(|(k, v)| (k, ByteString(v)))
+            .collect();
 
         self.record_event(
             ctx.client.clone(),
@@ -141,15 +312,10 @@ impl ConfigurationDiscoverer<ConfigMap> for ConfigMapClaim {
         .await;
 
         Ok(ConfigMap {
-            metadata: k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta {
-                name: Some(name),
-                namespace: Some(namespace),
-                owner_references: Some(vec![self.controller_owner_ref(&()).unwrap()]),
-                // finalizers: Some(vec![String::from("test.io/documents.kube.rs")]),
-                ..Default::default()
-            },
-            data: Some(data),
-            ..Default::default()
+            metadata,
+            data: Some(data.data),
+            binary_data: Some(binary_data).filter(|binary_data| !binary_data.is_empty()),
+            immutable: self.spec.target.immutable.filter(|immutable| *immutable),
         })
     }
 }
@@ -159,7 +325,7 @@ impl Default for ConfigMapClaim {
         ConfigMapClaim {
             // Provide default values for the necessary fields.
             // Adjust the fields as per your struct definition.
-            status: Some(ConfigurationSourceStatus::default()),
+            status: Some(ClaimStatus::default()),
             metadata: Default::default(),
             spec: Default::default(),
         }
@@ -180,6 +346,11 @@ impl Default for ClaimTargetRef {
         ClaimTargetRef {
             name: String::new(),
             creationPolicy: ClaimCreationPolicy::Merge,
+            force_conflicts: None,
+            template: None,
+            immutable: None,
+            history_limit: None,
+            rollout_targets: None,
         }
     }
 }
@@ -208,12 +379,19 @@ impl IReconcilable for ConfigMapClaim {
     version = "v1alpha1",
     kind = "SecretClaim",
     namespaced,
-    shortname = "sc"
+    shortname = "sc",
+    category = "externalconfig"
+)]
+#[kube(status = "ClaimStatus")]
+#[kube(
+    printcolumn = r#"{"name":"Ready","type":"string","jsonPath":".status.conditions[?(@.type==\"Ready\")].status"}"#,
+    printcolumn = r#"{"name":"Target","type":"string","jsonPath":".spec.target.name"}"#,
+    printcolumn = r#"{"name":"Last Synced","type":"date","jsonPath":".status.lastSynced"}"#,
+    printcolumn = r#"{"name":"Age","type":"date","jsonPath":".metadata.creationTimestamp"}"#
 )]
-#[kube(status = "ConfigurationSourceStatus")]
 pub struct SecretClaimSpec {
     pub data: HashMap<String, ClaimRef>,
-    pub target: ClaimTargetRef,
+    pub target: SecretClaimTargetRef,
     pub refreshInterval: Option<RefreshInterval>,
 }
 
@@ -228,49 +406,90 @@ impl Refreshable for SecretClaim {
 
 impl HasTarget for SecretClaim {
     fn get_target(&self) -> &ClaimTargetRef {
-        &self.spec.target
+        &self.spec.target.common
+    }
+}
+
+impl HasClaimData for SecretClaim {
+    fn get_claim_data(&self) -> &HashMap<String, ClaimRef> {
+        &self.spec.data
+    }
+    fn get_claim_status(&self) -> Option<&ClaimStatus> {
+        self.status.as_ref()
+    }
+    fn claim_api(&self, client: Client) -> Api<Self> {
+        Api::namespaced(
+            client,
+            &<Self as kube::ResourceExt>::namespace(self).unwrap(),
+        )
     }
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
-pub struct ConfigurationSourceStatus {
+/// Sync result of single data key
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DataKeyStatus {
+    pub key: String,
+    /// Stores the file was composed from, as `Kind/name`. With `Fallback` strategy the one that served it
+    pub stores: Option<Vec<String>>,
+    /// `sha256:` digest of the file content
+    pub hash: Option<String>,
     pub last_synced: Option<String>,
+    /// Error of last failed sync, cleared once key syncs again
+    pub last_error: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimStatus {
+    pub last_synced: Option<String>,
+    pub observed_generation: Option<i64>,
+    /// `Ready` and `Synced` conditions
+    pub conditions: Option<Vec<Condition>>,
+    pub data: Option<Vec<DataKeyStatus>>,
+    /// Name of target written by last successful sync, differs from `spec.target.name`
+    /// for immutable targets
+    pub target_name: Option<String>,
+    /// resourceVersion of target after last successful sync
+    pub target_resource_version: Option<String>,
+    /// Content hash rollout targets were last restarted for
+    pub rollout_hash: Option<String>,
 }
 
 impl Default for SecretClaimSpec {
     fn default() -> Self {
         SecretClaimSpec {
             data: HashMap::new(),
-            target: ClaimTargetRef::default(),
+            target: SecretClaimTargetRef {
+                common: ClaimTargetRef::default(),
+                secret_type: None,
+                key_mapping: None,
+            },
             refreshInterval: None,
         }
     }
 }
 
-impl Default for ConfigurationSourceStatus {
-    fn default() -> Self {
-        Self { last_synced: None }
-    }
-}
-
 #[async_trait]
 impl ConfigurationDiscoverer<Secret> for SecretClaim {
-    async fn create_resource_spec(&self, ctx: Arc<Context>) -> std::result::Result<Secret, Error> {
-        let name = self.spec.target.name.clone();
-        let namespace = <Self as kube::ResourceExt>::namespace(self).unwrap();
-        let mut data: BTreeMap<String, String> = BTreeMap::new();
-
-        for (file, refs) in &self.spec.data {
-            Self::compose_file(self, ctx.clone(), &refs, &namespace, file, &mut data).await?
-        }
+    async fn create_resource_spec(
+        &self,
+        ctx: Arc<Context>,
+        data: ComposedData,
+    ) -> std::result::Result<Secret, Error> {
+        let metadata = self.target_metadata();
 
-        let encoded_data: BTreeMap<String, ByteString> = data
-            .iter()
-            .map(|(k, v)| {
-                let enc = STANDARD.encode(v).into_bytes();
-                (k.clone(), ByteString(enc.to_vec()))
-            })
-            .collect();
+        let secret_type = self
+            .spec
+            .target
+            .secret_type
+            .clone()
+            .unwrap_or(SecretType::Opaque);
+        let data =
+            secret_type.build_data(data.into_bytes(), self.spec.target.key_mapping.as_ref())?;
+        // ByteString is base64 encoded on serialization
+        let encoded_data: BTreeMap<String, ByteString> =
+            data.into_iter().map(|(k, v)| (k, ByteString(v))).collect();
 
         self.record_event(
             ctx.client.clone(),
@@ -281,14 +500,15 @@ impl ConfigurationDiscoverer<Secret> for SecretClaim {
         .await;
 
         Ok(Secret {
-            metadata: k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta {
-                name: Some(name),
-                namespace: Some(namespace),
-                owner_references: Some(vec![self.controller_owner_ref(&()).unwrap()]),
-                // finalizers: Some(vec![String::from("test.io/documents.kube.rs")]),
-                ..Default::default()
-            },
+            metadata,
             data: Some(encoded_data),
+            type_: Some(secret_type.as_str().to_string()),
+            immutable: self
+                .spec
+                .target
+                .common
+                .immutable
+                .filter(|immutable| *immutable),
             ..Default::default()
         })
     }
@@ -299,7 +519,7 @@ impl Default for SecretClaim {
         SecretClaim {
             // Provide default values for the necessary fields.
             // Adjust the fields as per your struct definition.
-            status: Some(ConfigurationSourceStatus::default()),
+            status: Some(ClaimStatus::default()),
             metadata: Default::default(),
             spec: Default::default(),
         }
@@ -1,15 +1,34 @@
-use crate::contract::iconfigstore::IConfigStore;
+use crate::contract::clients::K8sClient;
 use crate::contract::lib::Error;
+use crate::controller::config_store::circuit_breaker::{
+    CircuitBreakerOptions, CircuitBreakerStore, CircuitState,
+};
 use crate::controller::config_store::http_store::{
-    HttpConfigStore, HttpConfigStoreConnectionDetails,
+    HttpAuthorization, HttpClientIdentity, HttpConfigStore, HttpConfigStoreConnectionDetails,
+    HttpTlsOptions, TlsVersion,
+};
+use crate::controller::config_store::retry::{
+    RetryOptions, DEFAULT_CONNECT_TIMEOUT, DEFAULT_REQUEST_TIMEOUT,
+};
+use crate::controller::config_store::store_registry::fingerprint;
+use crate::controller::config_store::vault_auth::{
+    VaultAuthMethod, VaultTokenSlot, SERVICE_ACCOUNT_TOKEN_PATH, VAULT_DEFAULT_APPROLE_MOUNT,
+    VAULT_DEFAULT_KUBERNETES_MOUNT,
 };
 use crate::controller::config_store::vault_store::{
-    VaultConfigStore, VaultConfigStoreConnectionDetails,
+    VaultConfigStore, VaultConfigStoreConnectionDetails, VaultKvVersion, VAULT_DEFAULT_MOUNT,
 };
+use crate::controller::utils::context::Context;
+use crate::controller::utils::crd::HumanDuration;
+use crate::controller::v1alpha1::crd_client::CrdClient;
 use async_trait::async_trait;
-use kube::{Client, CustomResource};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use kube::api::{Patch, PatchParams};
+use kube::{Api, Client, CustomResource, Resource, ResourceExt};
+use log::warn;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -17,20 +36,180 @@ use std::sync::Arc;
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum Provider {
-    Http(HttpConfig),
-    Vault(VaultConfig),
+    Http(Box<HttpConfig>),
+    Vault(Box<VaultConfig>),
 }
 
 impl Provider {
-    pub fn get_config_store(&self) -> Box<dyn IConfigStore> {
+    pub fn store_type(&self) -> &'static str {
+        match self {
+            Provider::Http(_) => "http",
+            Provider::Vault(_) => "vault",
+        }
+    }
+
+    /// Returns config store client registered for the store, building it when store changed.
+    /// Secrets referenced by the provider are resolved in the store's namespace, or in explicitly
+    /// given namespace in case of cluster scoped stores.
+    pub async fn get_config_store(
+        &self,
+        ctx: &Context,
+        namespace: Option<&str>,
+        store_uid: &str,
+        generation: Option<i64>,
+        circuit_breaker: &CircuitBreakerOptions,
+    ) -> Result<Arc<CircuitBreakerStore>, Error> {
         match &self {
-            Provider::Http(http_config) => Box::new(HttpConfigStore::new(
-                CrdConfigMapper::map_http_config(http_config.clone()),
-            )),
-            Provider::Vault(vault_config) => Box::new(VaultConfigStore::new(
-                CrdConfigMapper::map_vault_config(vault_config.clone()),
-            )),
+            Provider::Http(http_config) => {
+                let details = CrdConfigMapper::map_http_config(
+                    &ctx.api_client,
+                    namespace,
+                    *http_config.clone(),
+                )
+                .await?;
+                Ok(ctx.config_stores.get_or_build(
+                    store_uid,
+                    generation,
+                    fingerprint(&details),
+                    circuit_breaker,
+                    || Arc::new(HttpConfigStore::new(details)),
+                ))
+            }
+            Provider::Vault(vault_config) => {
+                let details = CrdConfigMapper::map_vault_config(
+                    &ctx.api_client,
+                    namespace,
+                    *vault_config.clone(),
+                )
+                .await?;
+                Ok(ctx.config_stores.get_or_build(
+                    store_uid,
+                    generation,
+                    fingerprint(&details),
+                    circuit_breaker,
+                    || Arc::new(VaultConfigStore::new(details, VaultTokenSlot::default())),
+                ))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretReference {
+    pub name: String,
+    /// Required for ClusterConfigurationStore. Namespaced stores may only reference own namespace.
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretKeySelector {
+    pub name: String,
+    /// Required for ClusterConfigurationStore. Namespaced stores may only reference own namespace.
+    pub namespace: Option<String>,
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigMapKeySelector {
+    pub name: String,
+    /// Required for ClusterConfigurationStore. Namespaced stores may only reference own namespace.
+    pub namespace: Option<String>,
+    pub key: String,
+}
+
+/// Resolves namespace the referenced object should be read from, relative to the referencing store.
+fn resolve_reference_namespace(
+    kind: &str,
+    name: &str,
+    namespace: Option<&str>,
+    store_namespace: Option<&str>,
+) -> Result<String, Error> {
+    match (store_namespace, namespace) {
+        (Some(store_ns), Some(ns)) if store_ns != ns => Err(Error::SecretReferenceError(format!(
+            "{} {}/{} is outside of store namespace {}",
+            kind, ns, name, store_ns
+        ))),
+        (Some(store_ns), _) => Ok(store_ns.to_string()),
+        (None, Some(ns)) => Ok(ns.to_string()),
+        (None, None) => Err(Error::SecretReferenceError(format!(
+            "{} {} referenced by cluster scoped store needs explicit namespace",
+            kind, name
+        ))),
+    }
+}
+
+impl SecretReference {
+    pub async fn get_value(
+        &self,
+        client: &CrdClient,
+        store_namespace: Option<&str>,
+        key: &str,
+    ) -> Result<String, Error> {
+        let namespace = resolve_reference_namespace(
+            "Secret",
+            &self.name,
+            self.namespace.as_deref(),
+            store_namespace,
+        )?;
+        let secret = client.get_secret(&self.name, &namespace).await?;
+
+        secret
+            .data
+            .as_ref()
+            .and_then(|data| data.get(key))
+            .map(|value| String::from_utf8_lossy(&value.0).to_string())
+            .ok_or_else(|| {
+                Error::SecretReferenceError(format!(
+                    "Key {} not found in Secret {}/{}",
+                    key, namespace, self.name
+                ))
+            })
+    }
+}
+
+impl SecretKeySelector {
+    pub async fn get_value(
+        &self,
+        client: &CrdClient,
+        store_namespace: Option<&str>,
+    ) -> Result<String, Error> {
+        SecretReference {
+            name: self.name.clone(),
+            namespace: self.namespace.clone(),
         }
+        .get_value(client, store_namespace, &self.key)
+        .await
+    }
+}
+
+impl ConfigMapKeySelector {
+    pub async fn get_value(
+        &self,
+        client: &CrdClient,
+        store_namespace: Option<&str>,
+    ) -> Result<String, Error> {
+        let namespace = resolve_reference_namespace(
+            "ConfigMap",
+            &self.name,
+            self.namespace.as_deref(),
+            store_namespace,
+        )?;
+        let config_map = client.get_config_map(&self.name, &namespace).await?;
+
+        config_map
+            .data
+            .as_ref()
+            .and_then(|data| data.get(&self.key))
+            .cloned()
+            .ok_or_else(|| {
+                Error::SecretReferenceError(format!(
+                    "Key {} not found in ConfigMap {}/{}",
+                    self.key, namespace, self.name
+                ))
+            })
     }
 }
 
@@ -40,16 +219,140 @@ impl Provider {
 pub struct HttpConfig {
     pub base_url: String,
     pub path: Option<String>,
+    /// Path probed by store controller, e.g. `/healthz`. Store is not probed when not set
+    pub health_path: Option<String>,
     pub protocol: Option<String>,
     pub headers: Option<HashMap<String, String>>,
     pub query_params: Option<HashMap<String, String>>,
+    pub auth: Option<HttpAuth>,
+    pub tls: Option<HttpTlsConfig>,
+    /// Defaults to `5s`
+    pub connect_timeout: Option<HumanDuration>,
+    /// Timeout of whole request including reading response body. Defaults to `5s`
+    pub request_timeout: Option<HumanDuration>,
+    pub retry: Option<RetryPolicy>,
+}
+
+/// Retries of failed fetches with jittered exponential backoff. Only server errors, connection
+/// errors and `429` responses with `Retry-After` are retried, other client errors fail right away.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryPolicy {
+    /// Defaults to `2`, `0` disables retries
+    pub max_retries: Option<u32>,
+    /// Defaults to `500ms`
+    pub initial_backoff: Option<HumanDuration>,
+    /// Upper bound of backoff and of accepted `Retry-After`. Defaults to `10s`
+    pub max_backoff: Option<HumanDuration>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpTlsConfig {
+    /// CA certificates trusted in addition to built in roots, e.g. for internal PKI
+    pub ca_bundle: Option<CaBundle>,
+    /// Overrides name used for SNI and certificate verification, connection still goes to `baseUrl` host
+    pub server_name: Option<String>,
+    /// Defaults to `1.2`
+    pub min_version: Option<TlsVersion>,
+}
+
+/// PEM encoded CA certificates. Exactly one of `pem`, `configMapRef` or `secretRef` must be set.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CaBundle {
+    pub pem: Option<String>,
+    pub config_map_ref: Option<ConfigMapKeySelector>,
+    pub secret_ref: Option<SecretKeySelector>,
+}
+
+/// Credentials read from Secrets on each fetch. `bearer` and `basic` are mutually exclusive,
+/// `clientCertificate` can be combined with either of them.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpAuth {
+    pub bearer: Option<HttpBearerAuth>,
+    pub basic: Option<HttpBasicAuth>,
+    pub client_certificate: Option<HttpClientCertificateAuth>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpBearerAuth {
+    pub secret_ref: SecretKeySelector,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpBasicAuth {
+    pub secret_ref: SecretReference,
+    /// Defaults to `username`
+    pub username_key: Option<String>,
+    /// Defaults to `password`
+    pub password_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpClientCertificateAuth {
+    pub secret_ref: SecretReference,
+    /// Defaults to `tls.crt`
+    pub certificate_key: Option<String>,
+    /// Defaults to `tls.key`
+    pub private_key_key: Option<String>,
 }
 
 // Define Vault-specific configuration
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct VaultConfig {
+    /// Vault address including scheme, e.g. `https://vault.vault.svc:8200`
     pub server: String,
+    /// Default KV mount, can be overridden with `mount` store parameter. Defaults to `secret`
+    pub mount: Option<String>,
+    pub kv_version: Option<VaultKvVersion>,
+    pub auth: Option<VaultAuth>,
+    /// Defaults to `5s`
+    pub connect_timeout: Option<HumanDuration>,
+    /// Defaults to `5s`
+    pub request_timeout: Option<HumanDuration>,
+    pub retry: Option<RetryPolicy>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum VaultAuth {
+    Kubernetes(VaultKubernetesAuth),
+    AppRole(VaultAppRoleAuth),
+    Token(VaultTokenAuth),
+}
+
+/// Login with operator's service account JWT. Token is always read from service account
+/// mount, so store cannot make the operator send other files to Vault.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultKubernetesAuth {
+    pub role: String,
+    /// Defaults to `kubernetes`
+    pub mount_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultAppRoleAuth {
+    /// Defaults to `approle`
+    pub mount_path: Option<String>,
+    pub secret_ref: SecretReference,
+    /// Defaults to `role_id`
+    pub role_id_key: Option<String>,
+    /// Defaults to `secret_id`
+    pub secret_id_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultTokenAuth {
+    pub secret_ref: SecretKeySelector,
 }
 
 #[derive(CustomResource, Debug, Clone, Deserialize, Serialize, JsonSchema)]
@@ -57,32 +360,202 @@ pub struct VaultConfig {
     group = "external-config.com",
     version = "v1alpha1",
     kind = "ConfigurationStore",
-    namespaced
+    namespaced,
+    category = "externalconfig"
 )]
 #[kube(status = "ConfigurationSourceStatus")]
+#[kube(
+    printcolumn = r#"{"name":"Ready","type":"string","jsonPath":".status.conditions[?(@.type==\"Ready\")].status"}"#,
+    printcolumn = r#"{"name":"Store Type","type":"string","jsonPath":".status.storeType"}"#,
+    printcolumn = r#"{"name":"HTTP","type":"string","jsonPath":".spec.provider.http.baseUrl"}"#,
+    printcolumn = r#"{"name":"Vault","type":"string","jsonPath":".spec.provider.vault.server"}"#,
+    printcolumn = r#"{"name":"Last Probe","type":"date","jsonPath":".status.lastProbeTime"}"#,
+    printcolumn = r#"{"name":"Error","type":"string","jsonPath":".status.lastError","priority":1}"#,
+    printcolumn = r#"{"name":"Age","type":"date","jsonPath":".metadata.creationTimestamp"}"#
+)]
+#[serde(rename_all = "camelCase")]
 pub struct ConfigurationStoreSpec {
     pub provider: Provider,
+    pub circuit_breaker: Option<CircuitBreakerPolicy>,
+    /// How often store controller probes the store. Defaults to `1m`
+    pub probe_interval: Option<HumanDuration>,
 }
 
 #[derive(CustomResource, Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[kube(
     group = "external-config.com",
     version = "v1alpha1",
-    kind = "ClusterConfigurationStore"
+    kind = "ClusterConfigurationStore",
+    category = "externalconfig"
 )]
 #[kube(status = "ConfigurationSourceStatus")]
+#[kube(
+    printcolumn = r#"{"name":"Ready","type":"string","jsonPath":".status.conditions[?(@.type==\"Ready\")].status"}"#,
+    printcolumn = r#"{"name":"Store Type","type":"string","jsonPath":".status.storeType"}"#,
+    printcolumn = r#"{"name":"HTTP","type":"string","jsonPath":".spec.provider.http.baseUrl"}"#,
+    printcolumn = r#"{"name":"Vault","type":"string","jsonPath":".spec.provider.vault.server"}"#,
+    printcolumn = r#"{"name":"Last Probe","type":"date","jsonPath":".status.lastProbeTime"}"#,
+    printcolumn = r#"{"name":"Error","type":"string","jsonPath":".status.lastError","priority":1}"#,
+    printcolumn = r#"{"name":"Age","type":"date","jsonPath":".metadata.creationTimestamp"}"#
+)]
+#[serde(rename_all = "camelCase")]
 pub struct ClusterConfigurationStoreSpec {
     pub provider: Provider,
+    pub circuit_breaker: Option<CircuitBreakerPolicy>,
+    /// How often store controller probes the store. Defaults to `1m`
+    pub probe_interval: Option<HumanDuration>,
+}
+
+/// Stops calling the store after consecutive failures, so claims referencing unavailable store
+/// fail fast instead of hammering it on every requeue.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitBreakerPolicy {
+    /// Consecutive server or connection failures that open the circuit. Defaults to `5`
+    pub failure_threshold: Option<u32>,
+    /// Time the circuit stays open before trial fetch is let through. Defaults to `30s`
+    pub cooldown: Option<HumanDuration>,
+}
+
+impl CircuitBreakerPolicy {
+    fn to_options(policy: Option<&CircuitBreakerPolicy>) -> CircuitBreakerOptions {
+        let defaults = CircuitBreakerOptions::default();
+        let Some(policy) = policy else {
+            return defaults;
+        };
+
+        CircuitBreakerOptions {
+            failure_threshold: policy
+                .failure_threshold
+                .unwrap_or(defaults.failure_threshold),
+            cooldown: policy
+                .cooldown
+                .as_ref()
+                .map(HumanDuration::as_std)
+                .unwrap_or(defaults.cooldown),
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigurationSourceStatus {
+    /// Time of last successful probe
     pub last_synced: Option<String>,
+    pub circuit_state: Option<CircuitState>,
+    pub observed_generation: Option<i64>,
+    /// `Ready` and `Degraded` conditions, set by store controller
+    pub conditions: Option<Vec<Condition>>,
+    pub last_probe_time: Option<String>,
+    /// Error of last failed probe, cleared once probe succeeds
+    pub last_error: Option<String>,
+    /// Provider kind, `http` or `vault`
+    pub store_type: Option<String>,
 }
 impl Default for ConfigurationSourceStatus {
     fn default() -> Self {
-        Self { last_synced: None }
+        Self {
+            last_synced: None,
+            circuit_state: None,
+            observed_generation: None,
+            conditions: None,
+            last_probe_time: None,
+            last_error: None,
+            store_type: None,
+        }
+    }
+}
+
+fn circuit_state_patch(state: CircuitState) -> Patch<serde_json::Value> {
+    Patch::Merge(json!({ "status": { "circuitState": state } }))
+}
+
+impl ConfigurationStore {
+    pub async fn get_config_store(&self, ctx: &Context) -> Result<Arc<CircuitBreakerStore>, Error> {
+        self.spec
+            .provider
+            .get_config_store(
+                ctx,
+                self.namespace().as_deref(),
+                &self.uid().unwrap_or_else(|| self.name_any()),
+                self.meta().generation,
+                &CircuitBreakerPolicy::to_options(self.spec.circuit_breaker.as_ref()),
+            )
+            .await
+    }
+
+    /// Exposes circuit breaker state in metrics and, when it changed, in store status.
+    pub async fn report_circuit_state(&self, ctx: &Context, state: CircuitState) {
+        let namespace = self.namespace().unwrap_or_default();
+        ctx.metrics.config_store.set_circuit_state(
+            "ConfigurationStore",
+            &namespace,
+            &self.name_any(),
+            state,
+        );
+
+        if self.status.as_ref().and_then(|status| status.circuit_state) == Some(state) {
+            return;
+        }
+        let api: Api<ConfigurationStore> = Api::namespaced((*ctx.client).clone(), &namespace);
+        if let Err(e) = api
+            .patch_status(
+                &self.name_any(),
+                &PatchParams::default(),
+                &circuit_state_patch(state),
+            )
+            .await
+        {
+            warn!(
+                "Failed to update circuit state of store {}: {}",
+                self.name_any(),
+                e
+            );
+        }
+    }
+}
+
+impl ClusterConfigurationStore {
+    pub async fn get_config_store(&self, ctx: &Context) -> Result<Arc<CircuitBreakerStore>, Error> {
+        self.spec
+            .provider
+            .get_config_store(
+                ctx,
+                None,
+                &self.uid().unwrap_or_else(|| self.name_any()),
+                self.meta().generation,
+                &CircuitBreakerPolicy::to_options(self.spec.circuit_breaker.as_ref()),
+            )
+            .await
+    }
+
+    /// Exposes circuit breaker state in metrics and, when it changed, in store status.
+    pub async fn report_circuit_state(&self, ctx: &Context, state: CircuitState) {
+        ctx.metrics.config_store.set_circuit_state(
+            "ClusterConfigurationStore",
+            "",
+            &self.name_any(),
+            state,
+        );
+
+        if self.status.as_ref().and_then(|status| status.circuit_state) == Some(state) {
+            return;
+        }
+        let api: Api<ClusterConfigurationStore> = Api::all((*ctx.client).clone());
+        if let Err(e) = api
+            .patch_status(
+                &self.name_any(),
+                &PatchParams::default(),
+                &circuit_state_patch(state),
+            )
+            .await
+        {
+            warn!(
+                "Failed to update circuit state of store {}: {}",
+                self.name_any(),
+                e
+            );
+        }
     }
 }
 
@@ -94,20 +567,215 @@ pub struct ConfigStoreFetcherAdapter {
 pub struct CrdConfigMapper {}
 
 impl CrdConfigMapper {
-    fn map_http_config(http_config: HttpConfig) -> HttpConfigStoreConnectionDetails {
-        HttpConfigStoreConnectionDetails {
+    async fn map_http_config(
+        client: &CrdClient,
+        namespace: Option<&str>,
+        http_config: HttpConfig,
+    ) -> Result<HttpConfigStoreConnectionDetails, Error> {
+        let (authorization, client_identity) = match &http_config.auth {
+            Some(auth) => (
+                Self::map_http_authorization(client, namespace, auth).await?,
+                Self::map_http_client_identity(client, namespace, auth).await?,
+            ),
+            None => (None, None),
+        };
+
+        let tls = match &http_config.tls {
+            Some(tls) => Self::map_http_tls(client, namespace, tls).await?,
+            None => HttpTlsOptions::default(),
+        };
+
+        Ok(HttpConfigStoreConnectionDetails {
             base_url: http_config.base_url.clone(),
             protocol: http_config.protocol.clone(),
             path: http_config.path.clone(),
+            health_path: http_config.health_path.clone(),
             headers: http_config.headers.unwrap_or(HashMap::new()),
             query_params: http_config.query_params.unwrap_or(HashMap::new()),
+            authorization,
+            client_identity,
+            tls,
+            connect_timeout: http_config
+                .connect_timeout
+                .map(|timeout| timeout.as_std())
+                .unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+            request_timeout: http_config
+                .request_timeout
+                .map(|timeout| timeout.as_std())
+                .unwrap_or(DEFAULT_REQUEST_TIMEOUT),
+            retry: Self::map_retry_policy(http_config.retry.as_ref()),
+        })
+    }
+    fn map_retry_policy(policy: Option<&RetryPolicy>) -> RetryOptions {
+        let defaults = RetryOptions::default();
+        let Some(policy) = policy else {
+            return defaults;
+        };
+
+        RetryOptions {
+            max_retries: policy.max_retries.unwrap_or(defaults.max_retries),
+            initial_backoff: policy
+                .initial_backoff
+                .as_ref()
+                .map(HumanDuration::as_std)
+                .unwrap_or(defaults.initial_backoff),
+            max_backoff: policy
+                .max_backoff
+                .as_ref()
+                .map(HumanDuration::as_std)
+                .unwrap_or(defaults.max_backoff),
+        }
+    }
+    async fn map_http_tls(
+        client: &CrdClient,
+        namespace: Option<&str>,
+        tls: &HttpTlsConfig,
+    ) -> Result<HttpTlsOptions, Error> {
+        let ca_bundle = match &tls.ca_bundle {
+            None => None,
+            Some(CaBundle {
+                pem: Some(pem),
+                config_map_ref: None,
+                secret_ref: None,
+            }) => Some(pem.clone()),
+            Some(CaBundle {
+                pem: None,
+                config_map_ref: Some(config_map_ref),
+                secret_ref: None,
+            }) => Some(config_map_ref.get_value(client, namespace).await?),
+            Some(CaBundle {
+                pem: None,
+                config_map_ref: None,
+                secret_ref: Some(secret_ref),
+            }) => Some(secret_ref.get_value(client, namespace).await?),
+            Some(_) => {
+                return Err(Error::InvalidConfigStore(String::from(
+                    "Exactly one of pem, configMapRef or secretRef must be set in caBundle",
+                )))
+            }
+        };
+
+        Ok(HttpTlsOptions {
+            ca_bundle,
+            server_name: tls.server_name.clone(),
+            min_version: tls.min_version,
+        })
+    }
+    async fn map_http_authorization(
+        client: &CrdClient,
+        namespace: Option<&str>,
+        auth: &HttpAuth,
+    ) -> Result<Option<HttpAuthorization>, Error> {
+        match (&auth.bearer, &auth.basic) {
+            (Some(_), Some(_)) => Err(Error::InvalidConfigStore(String::from(
+                "Only one of bearer or basic auth can be configured",
+            ))),
+            (Some(bearer), None) => Ok(Some(HttpAuthorization::Bearer(
+                bearer.secret_ref.get_value(client, namespace).await?,
+            ))),
+            (None, Some(basic)) => {
+                let username_key = basic.username_key.as_deref().unwrap_or("username");
+                let password_key = basic.password_key.as_deref().unwrap_or("password");
+
+                Ok(Some(HttpAuthorization::Basic {
+                    username: basic
+                        .secret_ref
+                        .get_value(client, namespace, username_key)
+                        .await?,
+                    password: basic
+                        .secret_ref
+                        .get_value(client, namespace, password_key)
+                        .await?,
+                }))
+            }
+            (None, None) => Ok(None),
         }
     }
-    fn map_vault_config(vault_config: VaultConfig) -> VaultConfigStoreConnectionDetails {
-        VaultConfigStoreConnectionDetails {
+    async fn map_http_client_identity(
+        client: &CrdClient,
+        namespace: Option<&str>,
+        auth: &HttpAuth,
+    ) -> Result<Option<HttpClientIdentity>, Error> {
+        let Some(certificate) = &auth.client_certificate else {
+            return Ok(None);
+        };
+        let certificate_key = certificate.certificate_key.as_deref().unwrap_or("tls.crt");
+        let private_key_key = certificate.private_key_key.as_deref().unwrap_or("tls.key");
+
+        Ok(Some(HttpClientIdentity {
+            certificate: certificate
+                .secret_ref
+                .get_value(client, namespace, certificate_key)
+                .await?,
+            private_key: certificate
+                .secret_ref
+                .get_value(client, namespace, private_key_key)
+                .await?,
+        }))
+    }
+    async fn map_vault_config(
+        client: &CrdClient,
+        namespace: Option<&str>,
+        vault_config: VaultConfig,
+    ) -> Result<VaultConfigStoreConnectionDetails, Error> {
+        let auth = match vault_config.auth {
+            Some(auth) => Some(Self::map_vault_auth(client, namespace, auth).await?),
+            None => None,
+        };
+
+        Ok(VaultConfigStoreConnectionDetails {
             url: vault_config.server.clone(),
+            mount: vault_config
+                .mount
+                .unwrap_or_else(|| VAULT_DEFAULT_MOUNT.to_string()),
+            kv_version: vault_config.kv_version.unwrap_or_default(),
             headers: HashMap::new(),
-            query_params: HashMap::new(),
+            auth,
+            connect_timeout: vault_config
+                .connect_timeout
+                .map(|timeout| timeout.as_std())
+                .unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+            request_timeout: vault_config
+                .request_timeout
+                .map(|timeout| timeout.as_std())
+                .unwrap_or(DEFAULT_REQUEST_TIMEOUT),
+            retry: Self::map_retry_policy(vault_config.retry.as_ref()),
+        })
+    }
+    async fn map_vault_auth(
+        client: &CrdClient,
+        namespace: Option<&str>,
+        auth: VaultAuth,
+    ) -> Result<VaultAuthMethod, Error> {
+        match auth {
+            VaultAuth::Kubernetes(kubernetes) => Ok(VaultAuthMethod::Kubernetes {
+                mount_path: kubernetes
+                    .mount_path
+                    .unwrap_or_else(|| VAULT_DEFAULT_KUBERNETES_MOUNT.to_string()),
+                role: kubernetes.role,
+                jwt_path: SERVICE_ACCOUNT_TOKEN_PATH.to_string(),
+            }),
+            VaultAuth::AppRole(app_role) => {
+                let role_id_key = app_role.role_id_key.as_deref().unwrap_or("role_id");
+                let secret_id_key = app_role.secret_id_key.as_deref().unwrap_or("secret_id");
+
+                Ok(VaultAuthMethod::AppRole {
+                    mount_path: app_role
+                        .mount_path
+                        .unwrap_or_else(|| VAULT_DEFAULT_APPROLE_MOUNT.to_string()),
+                    role_id: app_role
+                        .secret_ref
+                        .get_value(client, namespace, role_id_key)
+                        .await?,
+                    secret_id: app_role
+                        .secret_ref
+                        .get_value(client, namespace, secret_id_key)
+                        .await?,
+                })
+            }
+            VaultAuth::Token(token) => Ok(VaultAuthMethod::Token(
+                token.secret_ref.get_value(client, namespace).await?,
+            )),
         }
     }
 }
@@ -1,6 +1,17 @@
+pub mod claim_status;
+mod claim_status_tests;
+pub mod claim_triggers;
+mod claim_triggers_tests;
 pub mod configuration_discoverer;
+mod configuration_discoverer_tests;
 pub mod controller;
 mod controller_tests;
 pub mod crd;
 pub mod crd_client;
 pub mod fixtures;
+pub mod rollout;
+mod rollout_tests;
+pub mod secret_type;
+mod secret_type_tests;
+pub mod store_controller;
+mod store_controller_tests;
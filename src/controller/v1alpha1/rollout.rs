@@ -0,0 +1,117 @@
+use crate::contract::lib::{Error, Result};
+use crate::controller::v1alpha1::crd::claim::{RolloutTarget, RolloutTargetKind};
+use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, StatefulSet};
+use k8s_openapi::NamespaceResourceScope;
+use kube::api::{ListParams, Patch, PatchParams};
+use kube::core::ErrorResponse;
+use kube::{Api, Client, Resource, ResourceExt};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use std::fmt::Debug;
+use tracing::log::{info, warn};
+
+/// Annotation domain of pod templates restarted by claims, prefixed with kind of the claim.
+pub static ROLLOUT_ANNOTATION_DOMAIN: &str = "external-config.com";
+
+/// Pod template annotation holding content hash of the claim, e.g.
+/// `configmapclaim.external-config.com/app`. Name part is limited to 63 characters.
+pub fn rollout_annotation(claim_kind: &str, claim_name: &str) -> String {
+    let name: String = claim_name.chars().take(63).collect();
+    format!(
+        "{}.{}/{}",
+        claim_kind.to_lowercase(),
+        ROLLOUT_ANNOTATION_DOMAIN,
+        name.trim_end_matches(['-', '.', '_'])
+    )
+}
+
+/// Whether workloads have to be restarted for content hash. Workloads are only restarted
+/// when content changes after they were rolled out for previous one, so enabling rollout
+/// on a claim only records the hash.
+pub fn rollout_required(previous_hash: Option<&str>, hash: &str) -> bool {
+    previous_hash.is_some_and(|previous| previous != hash)
+}
+
+/// Picks workload by name or by label selector, never both.
+pub fn validate_rollout_target(target: &RolloutTarget) -> Result<(), Error> {
+    match (&target.name, &target.selector) {
+        (Some(_), None) | (None, Some(_)) => Ok(()),
+        _ => Err(Error::InvalidRolloutTarget(format!(
+            "{:?} must set exactly one of name or selector",
+            target.kind
+        ))),
+    }
+}
+
+/// Annotates pod template of every rollout target with content hash, which makes their
+/// controllers roll out new pods. Named workloads that do not exist are skipped.
+pub async fn rollout_workloads(
+    client: &Client,
+    namespace: &str,
+    targets: &[RolloutTarget],
+    annotation: &str,
+    hash: &str,
+) -> Result<()> {
+    targets.iter().try_for_each(validate_rollout_target)?;
+    for target in targets {
+        match target.kind {
+            RolloutTargetKind::Deployment => {
+                rollout::<Deployment>(client, namespace, target, annotation, hash).await?
+            }
+            RolloutTargetKind::StatefulSet => {
+                rollout::<StatefulSet>(client, namespace, target, annotation, hash).await?
+            }
+            RolloutTargetKind::DaemonSet => {
+                rollout::<DaemonSet>(client, namespace, target, annotation, hash).await?
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn rollout<K>(
+    client: &Client,
+    namespace: &str,
+    target: &RolloutTarget,
+    annotation: &str,
+    hash: &str,
+) -> Result<()>
+where
+    K: Resource<DynamicType = (), Scope = NamespaceResourceScope>
+        + Clone
+        + Debug
+        + DeserializeOwned,
+{
+    let api: Api<K> = Api::namespaced(client.clone(), namespace);
+    let names = match (&target.name, &target.selector) {
+        (Some(name), _) => vec![name.clone()],
+        (None, Some(selector)) => api
+            .list_metadata(&ListParams::default().labels(selector))
+            .await
+            .map_err(Error::KubeError)?
+            .into_iter()
+            .map(|workload| workload.name_any())
+            .collect(),
+        (None, None) => Vec::new(),
+    };
+
+    let patch = json!({
+        "spec": { "template": { "metadata": { "annotations": { annotation: hash } } } }
+    });
+    for name in names {
+        match api
+            .patch(&name, &PatchParams::default(), &Patch::Merge(&patch))
+            .await
+        {
+            Ok(_) => info!("Rolling out {:?} {}/{}", target.kind, namespace, name),
+            Err(kube::Error::Api(ErrorResponse { code: 404, .. })) => {
+                warn!(
+                    "Rollout target {:?} {}/{} does not exist",
+                    target.kind, namespace, name
+                )
+            }
+            Err(e) => return Err(Error::KubeError(e)),
+        }
+    }
+    Ok(())
+}
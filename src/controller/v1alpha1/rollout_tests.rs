@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::lib::Error;
+    use crate::controller::v1alpha1::crd::claim::{ConfigMapClaim, HasTarget, RolloutTargetKind};
+    use crate::controller::v1alpha1::rollout::{
+        rollout_annotation, rollout_required, validate_rollout_target,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_rollout_annotation_is_unique_per_claim() {
+        assert_eq!(
+            rollout_annotation("ConfigMapClaim", "app"),
+            "configmapclaim.external-config.com/app"
+        );
+        assert_ne!(
+            rollout_annotation("ConfigMapClaim", "app"),
+            rollout_annotation("SecretClaim", "app")
+        );
+
+        let long_name = format!("{}-{}", "a".repeat(62), "b");
+        let annotation = rollout_annotation("SecretClaim", &long_name);
+        let name = annotation.split('/').nth(1).unwrap();
+        assert_eq!(name, "a".repeat(62));
+    }
+
+    #[test]
+    fn test_workloads_are_restarted_only_when_content_changes() {
+        // Enabling rollout records hash without restarting
+        assert!(!rollout_required(None, "abc"));
+        assert!(!rollout_required(Some("abc"), "abc"));
+        assert!(rollout_required(Some("abc"), "def"));
+    }
+
+    #[test]
+    fn test_rollout_targets_are_picked_by_name_or_selector() {
+        let claim: ConfigMapClaim = serde_json::from_value(json!({
+            "apiVersion": "external-config.com/v1alpha1",
+            "kind": "ConfigMapClaim",
+            "metadata": { "name": "app", "namespace": "default" },
+            "spec": {
+                "target": {
+                    "name": "app-config",
+                    "creationPolicy": "Owned",
+                    "rolloutTargets": [
+                        { "kind": "Deployment", "name": "api" },
+                        { "kind": "StatefulSet", "selector": "app=db" },
+                        { "kind": "DaemonSet" },
+                        { "kind": "Deployment", "name": "api", "selector": "app=api" }
+                    ]
+                },
+                "data": {}
+            }
+        }))
+        .unwrap();
+        let targets = claim.get_target().rollout_targets.as_ref().unwrap();
+
+        assert_eq!(targets[1].kind, RolloutTargetKind::StatefulSet);
+        assert!(validate_rollout_target(&targets[0]).is_ok());
+        assert!(validate_rollout_target(&targets[1]).is_ok());
+        assert!(matches!(
+            validate_rollout_target(&targets[2]),
+            Err(Error::InvalidRolloutTarget(_))
+        ));
+        assert!(validate_rollout_target(&targets[3]).is_err());
+    }
+}
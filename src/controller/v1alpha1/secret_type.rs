@@ -0,0 +1,126 @@
+use crate::contract::lib::Error;
+use crate::controller::v1alpha1::crd::claim::SecretType;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+pub static DOCKER_CONFIG_JSON_KEY: &str = ".dockerconfigjson";
+
+impl SecretType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecretType::Opaque => "Opaque",
+            SecretType::Tls => "kubernetes.io/tls",
+            SecretType::DockerConfigJson => "kubernetes.io/dockerconfigjson",
+            SecretType::BasicAuth => "kubernetes.io/basic-auth",
+            SecretType::SshAuth => "kubernetes.io/ssh-auth",
+        }
+    }
+
+    /// Builds Secret data of this type from composed claim data. Keys are first renamed
+    /// according to `key_mapping` (secret key to claim data key), then required keys are
+    /// validated, so malformed Secret is never written.
+    pub fn build_data(
+        &self,
+        mut data: BTreeMap<String, Vec<u8>>,
+        key_mapping: Option<&BTreeMap<String, String>>,
+    ) -> Result<BTreeMap<String, Vec<u8>>, Error> {
+        for (secret_key, data_key) in key_mapping.into_iter().flatten() {
+            let value = data.remove(data_key).ok_or_else(|| {
+                Error::InvalidSecretData(format!(
+                    "key {} mapped to {} is not present in claim data",
+                    data_key, secret_key
+                ))
+            })?;
+            data.insert(secret_key.clone(), value);
+        }
+
+        if *self == SecretType::DockerConfigJson && !data.contains_key(DOCKER_CONFIG_JSON_KEY) {
+            build_docker_config_json(&mut data)?;
+        }
+
+        self.validate(&data)?;
+        Ok(data)
+    }
+
+    fn validate(&self, data: &BTreeMap<String, Vec<u8>>) -> Result<(), Error> {
+        let present = |key: &str| data.get(key).is_some_and(|value| !value.is_empty());
+        let missing: Vec<&str> = match self {
+            SecretType::Opaque => vec![],
+            SecretType::Tls => ["tls.crt", "tls.key"]
+                .into_iter()
+                .filter(|key| !present(key))
+                .collect(),
+            SecretType::DockerConfigJson => [DOCKER_CONFIG_JSON_KEY]
+                .into_iter()
+                .filter(|key| !present(key))
+                .collect(),
+            // API server requires at least one of the keys
+            SecretType::BasicAuth if !present("username") && !present("password") => {
+                vec!["username or password"]
+            }
+            SecretType::BasicAuth => vec![],
+            SecretType::SshAuth => ["ssh-privatekey"]
+                .into_iter()
+                .filter(|key| !present(key))
+                .collect(),
+        };
+        if !missing.is_empty() {
+            return Err(Error::InvalidSecretData(format!(
+                "{} Secret requires keys: {}",
+                self.as_str(),
+                missing.join(", ")
+            )));
+        }
+
+        if *self == SecretType::DockerConfigJson {
+            let config: serde_json::Value = serde_json::from_slice(&data[DOCKER_CONFIG_JSON_KEY])
+                .map_err(|e| {
+                Error::InvalidSecretData(format!(
+                    "{} is not valid JSON: {}",
+                    DOCKER_CONFIG_JSON_KEY, e
+                ))
+            })?;
+            if !config.get("auths").is_some_and(|auths| auths.is_object()) {
+                return Err(Error::InvalidSecretData(format!(
+                    "{} has no auths object",
+                    DOCKER_CONFIG_JSON_KEY
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builds `.dockerconfigjson` from `registry`, `username`, `password` and optional `email` keys.
+fn build_docker_config_json(data: &mut BTreeMap<String, Vec<u8>>) -> Result<(), Error> {
+    let text = |key: &str| {
+        data.get(key)
+            .map(|value| String::from_utf8_lossy(value).to_string())
+    };
+    let (Some(registry), Some(username), Some(password)) =
+        (text("registry"), text("username"), text("password"))
+    else {
+        return Err(Error::InvalidSecretData(format!(
+            "kubernetes.io/dockerconfigjson Secret requires {} key, or registry, username and password keys",
+            DOCKER_CONFIG_JSON_KEY
+        )));
+    };
+
+    let mut auth = json!({
+        "username": username,
+        "password": password,
+        "auth": STANDARD.encode(format!("{}:{}", username, password)),
+    });
+    if let Some(email) = text("email") {
+        auth["email"] = json!(email);
+    }
+    let config = json!({ "auths": { registry: auth } }).to_string();
+
+    for key in ["registry", "username", "password", "email"] {
+        data.remove(key);
+    }
+    data.insert(DOCKER_CONFIG_JSON_KEY.to_string(), config.into_bytes());
+    Ok(())
+}
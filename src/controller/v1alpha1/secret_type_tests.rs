@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::lib::Error;
+    use crate::controller::v1alpha1::crd::claim::SecretType;
+    use std::collections::BTreeMap;
+
+    fn mapping(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn data(entries: &[(&str, &str)]) -> BTreeMap<String, Vec<u8>> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.as_bytes().to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn test_tls_keys_are_mapped_from_claim_data() {
+        let mapping = mapping(&[("tls.crt", "cert.pem"), ("tls.key", "key.pem")]);
+
+        let secret = SecretType::Tls
+            .build_data(
+                data(&[("cert.pem", "CERT"), ("key.pem", "KEY")]),
+                Some(&mapping),
+            )
+            .expect("TLS secret should be built");
+
+        assert_eq!(secret, data(&[("tls.crt", "CERT"), ("tls.key", "KEY")]));
+    }
+
+    #[test]
+    fn test_missing_required_keys_are_rejected() {
+        match SecretType::Tls.build_data(data(&[("tls.crt", "CERT")]), None) {
+            Err(Error::InvalidSecretData(message)) => assert!(message.contains("tls.key")),
+            _ => panic!("Expected Error::InvalidSecretData"),
+        }
+        match SecretType::SshAuth.build_data(data(&[("ssh-privatekey", "")]), None) {
+            Err(Error::InvalidSecretData(message)) => assert!(message.contains("ssh-privatekey")),
+            _ => panic!("Expected Error::InvalidSecretData"),
+        }
+        assert!(SecretType::BasicAuth
+            .build_data(data(&[("username", "admin")]), None)
+            .is_ok());
+        assert!(SecretType::BasicAuth
+            .build_data(data(&[("other", "value")]), None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_docker_config_json_is_built_from_credentials() {
+        let secret = SecretType::DockerConfigJson
+            .build_data(
+                data(&[
+                    ("registry", "registry.internal"),
+                    ("username", "ci"),
+                    ("password", "secret"),
+                ]),
+                None,
+            )
+            .expect("Docker config should be built");
+
+        assert_eq!(secret.len(), 1);
+        let config: serde_json::Value =
+            serde_json::from_slice(&secret[".dockerconfigjson"]).unwrap();
+        let auth = &config["auths"]["registry.internal"];
+        assert_eq!(auth["username"], "ci");
+        assert_eq!(auth["auth"], "Y2k6c2VjcmV0");
+    }
+
+    #[test]
+    fn test_invalid_docker_config_json_is_rejected() {
+        match SecretType::DockerConfigJson
+            .build_data(data(&[(".dockerconfigjson", "{\"registry\": 1}")]), None)
+        {
+            Err(Error::InvalidSecretData(message)) => assert!(message.contains("auths")),
+            _ => panic!("Expected Error::InvalidSecretData"),
+        }
+    }
+}
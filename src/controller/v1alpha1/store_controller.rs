@@ -0,0 +1,281 @@
+use crate::contract::iconfigstore::IConfigStore;
+use crate::contract::lib::{Error, Result};
+use crate::controller::config_store::circuit_breaker::{CircuitBreakerStore, CircuitState};
+use crate::controller::utils::conditions::{
+    condition, set_condition, CONDITION_DEGRADED, CONDITION_READY,
+};
+use crate::controller::utils::context::Context;
+use crate::controller::utils::crd::HumanDuration;
+use crate::controller::v1alpha1::crd::configuration_store::{
+    ClusterConfigurationStore, ConfigurationSourceStatus, ConfigurationStore, Provider,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::stream::StreamExt;
+use kube::api::{Api, ListParams, Patch, PatchParams};
+use kube::runtime::controller::{Action, Controller};
+use kube::runtime::finalizer::{finalizer, Event as Finalizer};
+use kube::runtime::watcher::Config;
+use kube::{Client, Resource, ResourceExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+pub static DEFAULT_PROBE_INTERVAL: Duration = Duration::from_secs(60);
+/// Lets store controller release resources held for the store once it is deleted.
+pub static STORE_FINALIZER: &str = "external-config.com/config-store";
+
+/// ConfigurationStore and ClusterConfigurationStore, as seen by store controller.
+#[async_trait]
+pub trait IProbedStore:
+    Resource<DynamicType = ()>
+    + ResourceExt
+    + Clone
+    + Debug
+    + DeserializeOwned
+    + Serialize
+    + Send
+    + Sync
+    + 'static
+{
+    const KIND: &'static str;
+
+    fn store_api(&self, client: Client) -> Api<Self>;
+    fn store_status(&self) -> Option<&ConfigurationSourceStatus>;
+    fn probe_interval(&self) -> Option<&HumanDuration>;
+    fn provider(&self) -> &Provider;
+    async fn config_store(&self, ctx: &Context) -> Result<Arc<CircuitBreakerStore>>;
+}
+
+#[async_trait]
+impl IProbedStore for ConfigurationStore {
+    const KIND: &'static str = "ConfigurationStore";
+
+    fn store_api(&self, client: Client) -> Api<Self> {
+        Api::namespaced(client, &self.namespace().unwrap_or_default())
+    }
+    fn store_status(&self) -> Option<&ConfigurationSourceStatus> {
+        self.status.as_ref()
+    }
+    fn probe_interval(&self) -> Option<&HumanDuration> {
+        self.spec.probe_interval.as_ref()
+    }
+    fn provider(&self) -> &Provider {
+        &self.spec.provider
+    }
+    async fn config_store(&self, ctx: &Context) -> Result<Arc<CircuitBreakerStore>> {
+        self.get_config_store(ctx).await
+    }
+}
+
+#[async_trait]
+impl IProbedStore for ClusterConfigurationStore {
+    const KIND: &'static str = "ClusterConfigurationStore";
+
+    fn store_api(&self, client: Client) -> Api<Self> {
+        Api::all(client)
+    }
+    fn store_status(&self) -> Option<&ConfigurationSourceStatus> {
+        self.status.as_ref()
+    }
+    fn probe_interval(&self) -> Option<&HumanDuration> {
+        self.spec.probe_interval.as_ref()
+    }
+    fn provider(&self) -> &Provider {
+        &self.spec.provider
+    }
+    async fn config_store(&self, ctx: &Context) -> Result<Arc<CircuitBreakerStore>> {
+        self.get_config_store(ctx).await
+    }
+}
+
+/// Time left until next probe is due, or `None` when store should be probed now.
+/// Writing status triggers another reconcile, so recent probe of current generation is not repeated.
+pub fn next_probe_in(
+    status: Option<&ConfigurationSourceStatus>,
+    generation: Option<i64>,
+    interval: Duration,
+    now: DateTime<Utc>,
+) -> Option<Duration> {
+    let status = status?;
+    if status.observed_generation != generation {
+        return None;
+    }
+    let last_probe = DateTime::parse_from_rfc3339(status.last_probe_time.as_deref()?).ok()?;
+    let elapsed = (now - last_probe.with_timezone(&Utc))
+        .to_std()
+        .unwrap_or_default();
+    interval
+        .checked_sub(elapsed)
+        .filter(|remaining| !remaining.is_zero())
+}
+
+/// Status reflecting probe result. Store is `Ready` when its health check passed,
+/// and `Degraded` when it is not ready or its circuit breaker is not closed.
+pub fn probe_status(
+    previous: Option<&ConfigurationSourceStatus>,
+    generation: Option<i64>,
+    result: &Result<(), Error>,
+    circuit_state: Option<CircuitState>,
+    now: DateTime<Utc>,
+) -> ConfigurationSourceStatus {
+    let mut status = previous.cloned().unwrap_or_default();
+    let mut conditions = status.conditions.take().unwrap_or_default();
+    let timestamp = now.to_rfc3339();
+
+    status.observed_generation = generation;
+    status.last_probe_time = Some(timestamp.clone());
+    status.circuit_state = circuit_state.or(status.circuit_state);
+
+    match result {
+        Ok(()) => {
+            status.last_synced = Some(timestamp);
+            status.last_error = None;
+            set_condition(
+                &mut conditions,
+                condition(CONDITION_READY, true, "ProbeSucceeded", "", generation),
+            );
+            let circuit_closed =
+                circuit_state.unwrap_or(CircuitState::Closed) == CircuitState::Closed;
+            set_condition(
+                &mut conditions,
+                if circuit_closed {
+                    condition(CONDITION_DEGRADED, false, "ProbeSucceeded", "", generation)
+                } else {
+                    condition(
+                        CONDITION_DEGRADED,
+                        true,
+                        "CircuitOpen",
+                        "Fetches from the store failed repeatedly, circuit breaker is not closed",
+                        generation,
+                    )
+                },
+            );
+        }
+        Err(e) => {
+            let message = e.to_string();
+            status.last_error = Some(message.clone());
+            set_condition(
+                &mut conditions,
+                condition(CONDITION_READY, false, "ProbeFailed", &message, generation),
+            );
+            set_condition(
+                &mut conditions,
+                condition(
+                    CONDITION_DEGRADED,
+                    true,
+                    "ProbeFailed",
+                    &message,
+                    generation,
+                ),
+            );
+        }
+    }
+
+    status.conditions = Some(conditions);
+    status
+}
+
+pub async fn reconcile<T: IProbedStore>(store: Arc<T>, ctx: Arc<Context>) -> Result<Action> {
+    let api = store.store_api((*ctx.client).clone());
+    finalizer(&api, STORE_FINALIZER, store, |event| async {
+        match event {
+            Finalizer::Apply(store) => probe(store, ctx.clone()).await,
+            Finalizer::Cleanup(store) => {
+                info!("Releasing {} {}", T::KIND, store.name_any());
+                let store_uid = store.uid().unwrap_or_else(|| store.name_any());
+                ctx.config_stores.evict(&store_uid);
+                ctx.parsed_content.evict(&store_uid);
+                Ok(Action::await_change())
+            }
+        }
+    })
+    .await
+    .map_err(|e| Error::FinalizerError(Box::new(e)))
+}
+
+async fn probe<T: IProbedStore>(store: Arc<T>, ctx: Arc<Context>) -> Result<Action> {
+    let interval = store
+        .probe_interval()
+        .map(HumanDuration::as_std)
+        .unwrap_or(DEFAULT_PROBE_INTERVAL);
+    let generation = store.meta().generation;
+
+    if let Some(remaining) = next_probe_in(store.store_status(), generation, interval, Utc::now()) {
+        return Ok(Action::requeue(remaining));
+    }
+
+    let (result, circuit_state) = match store.config_store(&ctx).await {
+        Ok(config_store) => (
+            config_store.health_check().await,
+            Some(config_store.state()),
+        ),
+        Err(e) => (Err(e), None),
+    };
+    if let Err(e) = &result {
+        warn!("Probe of {} {} failed: {}", T::KIND, store.name_any(), e);
+    }
+    if let Some(state) = circuit_state {
+        ctx.metrics.config_store.set_circuit_state(
+            T::KIND,
+            &store.namespace().unwrap_or_default(),
+            &store.name_any(),
+            state,
+        );
+    }
+
+    let mut status = probe_status(
+        store.store_status(),
+        generation,
+        &result,
+        circuit_state,
+        Utc::now(),
+    );
+    status.store_type = Some(store.provider().store_type().to_string());
+    store
+        .store_api((*ctx.client).clone())
+        .patch_status(
+            &store.name_any(),
+            &PatchParams::default(),
+            &Patch::Merge(json!({ "status": status })),
+        )
+        .await
+        .map_err(Error::KubeError)?;
+
+    Ok(Action::requeue(interval))
+}
+
+pub fn error_policy<T: IProbedStore>(store: Arc<T>, error: &Error, _ctx: Arc<Context>) -> Action {
+    error!(
+        "Error probing {} {}: {:?}",
+        T::KIND,
+        store.name_any(),
+        error
+    );
+    Action::requeue(Duration::from_secs(60))
+}
+
+/// Periodically probes stores of given kind, reporting their health in status.
+pub async fn run<T: IProbedStore>(data: Context) {
+    let api: Api<T> = Api::all((*data.client).clone());
+
+    if let Err(e) = api.list(&ListParams::default().limit(1)).await {
+        error!("CRD is not queryable; {e:?}. Is the CRD installed?");
+        std::process::exit(1);
+    }
+
+    Controller::new(api, Config::default())
+        .shutdown_on_signal()
+        .run(reconcile::<T>, error_policy::<T>, Arc::new(data))
+        .for_each(|res| async move {
+            match res {
+                Ok(o) => info!("Probed {:?}", o),
+                Err(e) => error!("Probe failed: {:?}", e),
+            }
+        })
+        .await;
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::lib::Error;
+    use crate::controller::config_store::circuit_breaker::CircuitState;
+    use crate::controller::v1alpha1::store_controller::{next_probe_in, probe_status};
+    use chrono::{TimeDelta, Utc};
+    use std::time::Duration;
+
+    fn condition_status(
+        status: &crate::controller::v1alpha1::crd::configuration_store::ConfigurationSourceStatus,
+        type_: &str,
+    ) -> (String, String) {
+        let condition = status
+            .conditions
+            .as_ref()
+            .and_then(|conditions| conditions.iter().find(|c| c.type_ == type_))
+            .expect("Condition should be set");
+        (condition.status.clone(), condition.reason.clone())
+    }
+
+    #[test]
+    fn test_successful_probe_marks_store_ready() {
+        let status = probe_status(
+            None,
+            Some(1),
+            &Ok(()),
+            Some(CircuitState::Closed),
+            Utc::now(),
+        );
+
+        assert_eq!(
+            condition_status(&status, "Ready"),
+            ("True".into(), "ProbeSucceeded".into())
+        );
+        assert_eq!(condition_status(&status, "Degraded").0, "False");
+        assert_eq!(status.observed_generation, Some(1));
+        assert!(status.last_synced.is_some());
+        assert!(status.last_error.is_none());
+    }
+
+    #[test]
+    fn test_open_circuit_marks_store_degraded() {
+        let status = probe_status(None, Some(1), &Ok(()), Some(CircuitState::Open), Utc::now());
+
+        assert_eq!(condition_status(&status, "Ready").0, "True");
+        assert_eq!(
+            condition_status(&status, "Degraded"),
+            ("True".into(), "CircuitOpen".into())
+        );
+    }
+
+    #[test]
+    fn test_failed_probe_keeps_last_synced_and_records_error() {
+        let healthy = probe_status(
+            None,
+            Some(1),
+            &Ok(()),
+            Some(CircuitState::Closed),
+            Utc::now(),
+        );
+        let result = Err(Error::HttpConfigStoreServerError(std::io::Error::other(
+            "unavailable",
+        )));
+
+        let status = probe_status(Some(&healthy), Some(1), &result, None, Utc::now());
+
+        assert_eq!(
+            condition_status(&status, "Ready"),
+            ("False".into(), "ProbeFailed".into())
+        );
+        assert_eq!(status.last_synced, healthy.last_synced);
+        assert_eq!(status.circuit_state, Some(CircuitState::Closed));
+        assert!(status.last_error.unwrap().contains("unavailable"));
+    }
+
+    #[test]
+    fn test_recent_probe_of_current_generation_is_not_repeated() {
+        let now = Utc::now();
+        let interval = Duration::from_secs(60);
+        let probed = probe_status(
+            None,
+            Some(1),
+            &Ok(()),
+            Some(CircuitState::Closed),
+            now - TimeDelta::seconds(20),
+        );
+
+        let remaining = next_probe_in(Some(&probed), Some(1), interval, now).unwrap();
+        assert!(remaining <= Duration::from_secs(40) && remaining > Duration::from_secs(39));
+
+        assert!(next_probe_in(Some(&probed), Some(2), interval, now).is_none());
+        assert!(next_probe_in(
+            Some(&probed),
+            Some(1),
+            interval,
+            now + TimeDelta::seconds(60)
+        )
+        .is_none());
+        assert!(next_probe_in(None, Some(1), interval, now).is_none());
+    }
+}
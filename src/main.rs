@@ -1,22 +1,14 @@
-use futures::join;
-use kube::{Api, Client};
+use kube::Client;
 use std::sync::Arc;
-use std::thread::sleep;
-use std::time::Duration;
-use tokio::signal::unix::{signal, SignalKind};
-use tracing::{error, info, Level};
+use tracing::{error, info};
 
 use crate::controller::controller_data::State;
 use crate::controller::leader_election::leader_election::LeaderElection;
 use crate::controller::utils::signals::notify_cancellation_token;
 use crate::controller::v1alpha1;
-use crate::controller::v1alpha1::crd_client::CrdClient;
 use crate::observability::metrics_server::run_metrics_server;
 use crate::observability::telemetry;
-use controller::utils::context::Context;
-use futures::stream::StreamExt;
-use tokio::signal;
-use tokio::sync::{mpsc, Notify};
+use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
 mod contract;
@@ -43,46 +35,51 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     //     .with_max_level(Level::INFO)
     //     .init();
 
-    let (shutdown_send, mut shutdown_recv) = mpsc::unbounded_channel::<()>();
+    // Sender is kept alive, channel closed by dropping it would cancel the token right away
+    let (_shutdown_send, shutdown_recv) = mpsc::unbounded_channel::<()>();
 
     let token = Arc::new(CancellationToken::new());
     notify_cancellation_token(&token, shutdown_recv);
 
     let leader_elector = Arc::new(LeaderElection::new(token.clone(), context.clone()));
 
-    if leader_elector.enabled() {
-        let lease = leader_elector.claim_leadership_loop().await;
-
-        match lease {
-            Ok(Some(lease)) => {
-                tokio::spawn(async move {
-                    leader_elector.refresh_leadership_loop().await;
-                });
-            }
-            Ok(None) => {
-                info!("No k8s env variables set. Startus controller...");
-            }
-            Err(e) => {
-                info!(
-                    "There was some error when trying to claim lease. Closing... {:?}",
-                    e
-                );
-                token.cancel()
-            }
+    if !leader_elector.enabled() {
+        info!("No k8s env variables set. Starting controllers without leader election...");
+        tokio::select! {
+            _ = v1alpha1::controller::run((*context).clone()) => {},
+            _ = token.cancelled() => {
+                info!("Shutdown signal received. Closing...");
+            },
         }
+        return Ok(());
     }
+    leader_elector.config().validate()?;
 
-    //Wait for a moment in case close signal was passed.
-    let mut interval = tokio::time::interval(Duration::from_millis(100));
-    tokio::select! {
-        _ = interval.tick() => {
-            join![
-                v1alpha1::controller::run((*context).clone())
-            ];
-        },
-        _ = token.cancelled() => {
-            info!("Shutdown signal received, before controllers started. Closing...");
-        },
+    // Replica runs controllers only while it holds the lease, afterwards it stands by again
+    loop {
+        if let Err(e) = leader_elector.claim_leadership_loop().await {
+            info!("Stopped waiting for leadership. Closing... {:?}", e);
+            break;
+        }
+        // Controllers stop on shutdown signal too, possibly before the token is cancelled
+        let stopped = tokio::select! {
+            _ = v1alpha1::controller::run((*context).clone()) => {
+                info!("Controllers stopped. Releasing leadership...");
+                true
+            },
+            _ = leader_elector.refresh_leadership_loop() => {
+                error!("Leadership lost, stopping controllers");
+                false
+            },
+            _ = token.cancelled() => {
+                info!("Shutdown signal received. Releasing leadership...");
+                true
+            },
+        };
+        if stopped {
+            leader_elector.release().await;
+            break;
+        }
     }
 
     Ok(())
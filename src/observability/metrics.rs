@@ -1,9 +1,10 @@
 use crate::contract::lib::Error;
+use crate::controller::config_store::circuit_breaker::CircuitState;
 use kube::ResourceExt;
 use opentelemetry::trace::TraceId;
 use prometheus_client::{
     encoding::EncodeLabelSet,
-    metrics::{counter::Counter, exemplar::HistogramWithExemplars, family::Family},
+    metrics::{counter::Counter, exemplar::HistogramWithExemplars, family::Family, gauge::Gauge},
     registry::{Registry, Unit},
 };
 use std::sync::Arc;
@@ -12,6 +13,7 @@ use tokio::time::Instant;
 #[derive(Clone)]
 pub struct Metrics {
     pub reconcile: ReconcileMetrics,
+    pub config_store: ConfigStoreMetrics,
     pub registry: Arc<Registry>,
 }
 
@@ -19,9 +21,11 @@ impl Default for Metrics {
     fn default() -> Self {
         let mut registry = Registry::with_prefix("doc_ctrl_reconcile");
         let reconcile = ReconcileMetrics::default().register(&mut registry);
+        let config_store = ConfigStoreMetrics::default().register(&mut registry);
         Self {
             registry: Arc::new(registry),
             reconcile,
+            config_store,
         }
     }
 }
@@ -101,6 +105,65 @@ impl ReconcileMetrics {
     }
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct StoreLabels {
+    pub kind: String,
+    pub namespace: String,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct CacheLabels {
+    pub kind: String,
+    pub namespace: String,
+    pub name: String,
+    /// `hit` when store reported content unchanged, `miss` when it was downloaded
+    pub result: String,
+}
+
+#[derive(Clone, Default)]
+pub struct ConfigStoreMetrics {
+    pub circuit_state: Family<StoreLabels, Gauge>,
+    pub cache_lookups: Family<CacheLabels, Counter>,
+}
+
+impl ConfigStoreMetrics {
+    pub fn register(self, r: &mut Registry) -> Self {
+        r.register(
+            "config_store_circuit_state",
+            "circuit breaker state of config store (0 closed, 1 half-open, 2 open)",
+            self.circuit_state.clone(),
+        );
+        r.register(
+            "config_store_cache_lookups",
+            "conditional fetches of config store content by result (hit, miss)",
+            self.cache_lookups.clone(),
+        );
+        self
+    }
+
+    pub fn set_circuit_state(&self, kind: &str, namespace: &str, name: &str, state: CircuitState) {
+        self.circuit_state
+            .get_or_create(&StoreLabels {
+                kind: kind.to_string(),
+                namespace: namespace.to_string(),
+                name: name.to_string(),
+            })
+            .set(state.as_gauge());
+    }
+
+    pub fn record_cache_lookup(&self, kind: &str, namespace: &str, name: &str, hit: bool) {
+        self.cache_lookups
+            .get_or_create(&CacheLabels {
+                kind: kind.to_string(),
+                namespace: namespace.to_string(),
+                name: name.to_string(),
+                result: String::from(if hit { "hit" } else { "miss" }),
+            })
+            .inc();
+    }
+}
+
 /// Smart function duration measurer
 ///
 /// Relies on Drop to calculate duration and register the observation in the histogram
@@ -1,10 +1,21 @@
 #![allow(unused_imports, unused_variables)]
 use crate::contract::lib::Error;
 use crate::controller::controller_data::State;
+use crate::controller::utils::config::Config;
+use crate::controller::utils::webhook::{
+    SignedRequest, StoreWebhook, WebhookRejection, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
+use crate::controller::v1alpha1::claim_triggers::StoreChange;
+use crate::controller::v1alpha1::crd::configuration_store::{
+    ClusterConfigurationStore, ConfigurationStore,
+};
 use actix_web::dev::Server;
+use actix_web::web::{Bytes, Path};
 use actix_web::{
-    get, middleware, web::Data, App, HttpRequest, HttpResponse, HttpServer, Responder,
+    get, middleware, post, web::Data, App, HttpRequest, HttpResponse, HttpServer, Responder,
 };
+use kube::Resource;
+use std::time::{Instant, SystemTime};
 
 #[get("/metrics")]
 async fn metrics(c: Data<State>, _req: HttpRequest) -> impl Responder {
@@ -25,14 +36,106 @@ async fn index(c: Data<State>, _req: HttpRequest) -> impl Responder {
     HttpResponse::Ok().json(&d)
 }
 
+/// Notifies that ConfigurationStore changed, syncing claims using it. Request is signed with
+/// `WEBHOOK_SECRET`: signature of Unix timestamp, method, path and body (see `webhook::sign`)
+/// is passed in `X-Signature-256` header as `sha256=<hex>`, timestamp in
+/// `X-Signature-Timestamp` header. Requests signed more than 5 minutes ago are rejected.
+#[post("/webhook/stores/{namespace}/{name}")]
+async fn store_webhook(
+    c: Data<State>,
+    webhook: Data<StoreWebhook>,
+    path: Path<(String, String)>,
+    req: HttpRequest,
+    body: Bytes,
+) -> impl Responder {
+    let (namespace, name) = path.into_inner();
+    let change = StoreChange {
+        kind: ConfigurationStore::kind(&()).to_string(),
+        namespace: Some(namespace),
+        name,
+    };
+    notify_store_change(&c, &webhook, &req, &body, change)
+}
+
+/// Notifies that ClusterConfigurationStore changed, see `store_webhook`.
+#[post("/webhook/clusterstores/{name}")]
+async fn cluster_store_webhook(
+    c: Data<State>,
+    webhook: Data<StoreWebhook>,
+    path: Path<String>,
+    req: HttpRequest,
+    body: Bytes,
+) -> impl Responder {
+    let change = StoreChange {
+        kind: ClusterConfigurationStore::kind(&()).to_string(),
+        namespace: None,
+        name: path.into_inner(),
+    };
+    notify_store_change(&c, &webhook, &req, &body, change)
+}
+
+fn notify_store_change(
+    state: &State,
+    webhook: &StoreWebhook,
+    req: &HttpRequest,
+    body: &[u8],
+    change: StoreChange,
+) -> HttpResponse {
+    let header = |name| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    let request = SignedRequest {
+        method: req.method().as_str(),
+        path: req.path(),
+        timestamp: header(TIMESTAMP_HEADER),
+        body,
+        signature: header(SIGNATURE_HEADER),
+    };
+
+    if let Err(rejection) = webhook.authenticate(&request, SystemTime::now()) {
+        return rejected(rejection);
+    }
+    let store = change.to_string();
+    match webhook.notify(&store, Instant::now(), || state.notify_store_change(change)) {
+        Ok(true) => HttpResponse::Accepted().finish(),
+        // Only leader runs controllers, caller should retry against another replica
+        Ok(false) => HttpResponse::ServiceUnavailable().json("controllers are not running"),
+        Err(rejection) => rejected(rejection),
+    }
+}
+
+fn rejected(rejection: WebhookRejection) -> HttpResponse {
+    match rejection {
+        WebhookRejection::Disabled => HttpResponse::NotFound().finish(),
+        WebhookRejection::InvalidSignature => {
+            HttpResponse::Unauthorized().json("invalid signature")
+        }
+        WebhookRejection::StaleTimestamp => {
+            HttpResponse::Unauthorized().json("missing or stale timestamp")
+        }
+        WebhookRejection::RateLimited(retry_after) => HttpResponse::TooManyRequests()
+            .insert_header(("Retry-After", retry_after.as_secs_f64().ceil().to_string()))
+            .finish(),
+    }
+}
+
 pub fn run_metrics_server(state: State) -> Result<Server, Error> {
+    let webhook = Data::new(StoreWebhook::new(
+        Config::webhook_secret(),
+        Config::webhook_min_interval(),
+    ));
     let server = HttpServer::new(move || {
         App::new()
             .app_data(Data::new(state.clone()))
+            .app_data(webhook.clone())
             .wrap(middleware::Logger::default().exclude("/health"))
             .service(index)
             .service(health)
             .service(metrics)
+            .service(store_webhook)
+            .service(cluster_store_webhook)
     })
     .workers(1)
     .bind("0.0.0.0:8080")